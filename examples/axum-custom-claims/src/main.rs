@@ -7,7 +7,6 @@ use axum::{
 };
 use firebase_auth::{FirebaseAuth, FirebaseAuthState, FirebaseProvider};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct FirebaseUser {
@@ -61,14 +60,9 @@ where
             .and_then(|value| value.to_str().ok())
             .unwrap_or("");
 
-        let bearer = get_bearer_token(auth_header).map_or(
-            Err(UnauthorizedResponse {
-                msg: "Missing Bearer Token".to_string(),
-            }),
-            Ok,
-        )?;
-
-        debug!("Got bearer token {}", bearer);
+        let bearer = get_bearer_token(auth_header).ok_or(UnauthorizedResponse {
+            msg: "Missing Bearer Token".to_string(),
+        })?;
 
         match store.firebase_auth.verify(&bearer) {
             Err(e) => Err(UnauthorizedResponse {
@@ -1,10 +1,16 @@
 use actix_web::error::ErrorUnauthorized;
 use actix_web::{dev, http::header::Header, web, Error, FromRequest, HttpRequest};
 use actix_web_httpauth::headers::authorization::{Authorization, Bearer};
-use futures::future::{err, ok, Ready};
-use tracing::debug;
+use futures::future::{err, ok, LocalBoxFuture, Ready};
+use serde::de::DeserializeOwned;
 
-use crate::{FirebaseAuth, FirebaseUser};
+use crate::{
+    AppCheck, AppCheckToken, FirebaseAuth, FirebaseUser, GoogleOidcAuth, GoogleUser, IapAuth,
+    IapUser, JwtClaims, JwtVerifier, SingleUse, VerificationError, VerifiedToken, VerifyOptions,
+};
+
+const IAP_JWT_HEADER: &str = "x-goog-iap-jwt-assertion";
+const APP_CHECK_HEADER: &str = "x-firebase-appcheck";
 
 fn get_bearer_token(header: &str) -> Option<String> {
     let prefix_len = "Bearer ".len();
@@ -15,25 +21,170 @@ fn get_bearer_token(header: &str) -> Option<String> {
     }
 }
 
+fn get_firebase_auth(req: &HttpRequest) -> &web::Data<FirebaseAuth> {
+    req.app_data::<web::Data<FirebaseAuth>>()
+        .expect("must init FirebaseAuth in Application Data. see description in https://crates.io/crates/firebase-auth")
+}
+
+/// The [`VerifyOptions`] added to the resource or scope with `.app_data(options)`.
+fn verify_options(req: &HttpRequest) -> VerifyOptions {
+    req.app_data::<VerifyOptions>().cloned().unwrap_or_default()
+}
+
+fn bearer_token(req: &HttpRequest) -> Result<String, Error> {
+    let authorization = Authorization::<Bearer>::parse(req)?;
+    Ok(get_bearer_token(&authorization.to_string()).unwrap_or_default())
+}
+
+fn rejected(e: VerificationError) -> Error {
+    ErrorUnauthorized(format!("Failed to verify Token {}", e))
+}
+
+/// Verify the bearer token with the [`VerifyOptions`] of the request.
+fn verify_bearer<T>(
+    req: &HttpRequest,
+    verify: impl FnOnce(&str, &VerifyOptions) -> Result<T, VerificationError>,
+) -> Ready<Result<T, Error>> {
+    let bearer = match bearer_token(req) {
+        Err(e) => return err(e),
+        Ok(v) => v,
+    };
+
+    match verify(&bearer, &verify_options(req)) {
+        Err(e) => err(rejected(e)),
+        Ok(claims) => ok(claims),
+    }
+}
+
 impl FromRequest for FirebaseUser {
     type Error = Error;
     type Future = Ready<Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
-        let firebase_auth = req
-                .app_data::<web::Data<FirebaseAuth>>()
-                .expect("must init FirebaseAuth in Application Data. see description in https://crates.io/crates/firebase-auth");
+        let firebase_auth = get_firebase_auth(req);
+        verify_bearer(req, |token, options| {
+            firebase_auth.verify_with(token, options)
+        })
+    }
+}
 
-        let bearer = match Authorization::<Bearer>::parse(req) {
-            Err(e) => return err(e.into()),
-            Ok(v) => get_bearer_token(&v.to_string()).unwrap_or_default(),
-        };
+/// Requires a `Data<GoogleOidcAuth>` in the application data.
+impl FromRequest for GoogleUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
+        let google_auth = req
+            .app_data::<web::Data<GoogleOidcAuth>>()
+            .expect("must init GoogleOidcAuth in Application Data to extract GoogleUser");
+        verify_bearer(req, |token, options| {
+            google_auth.verify_with(token, options)
+        })
+    }
+}
+
+/// Requires a `Data<JwtVerifier>` in the application data.
+impl<T: DeserializeOwned> FromRequest for JwtClaims<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
+        let verifier = req
+            .app_data::<web::Data<JwtVerifier>>()
+            .expect("must init JwtVerifier in Application Data to extract JwtClaims");
+        verify_bearer(req, |token, options| {
+            verifier.verify_with(token, options).map(JwtClaims)
+        })
+    }
+}
+
+/// Requires a `Data<IapAuth>` in the application data.
+impl FromRequest for IapUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
+        let iap_auth = req
+            .app_data::<web::Data<IapAuth>>()
+            .expect("must init IapAuth in Application Data to extract IapUser");
 
-        debug!("Got bearer token {}", bearer);
+        let assertion = match req
+            .headers()
+            .get(IAP_JWT_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(v) => v,
+            None => return err(ErrorUnauthorized("Missing IAP Jwt Assertion")),
+        };
 
-        match firebase_auth.verify(&bearer) {
-            Err(e) => err(ErrorUnauthorized(format!("Failed to verify Token {}", e))),
+        match iap_auth.verify_with(assertion, &verify_options(req)) {
+            Err(e) => err(rejected(e)),
             Ok(user) => ok(user),
         }
     }
 }
+
+impl<T: DeserializeOwned + 'static> FromRequest for SingleUse<T> {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
+        let firebase_auth = get_firebase_auth(req).clone();
+        let options = verify_options(req);
+
+        let bearer = match bearer_token(req) {
+            Err(e) => return Box::pin(err(e)),
+            Ok(v) => v,
+        };
+
+        Box::pin(async move {
+            firebase_auth
+                .verify_once_with(&bearer, &options)
+                .await
+                .map(SingleUse)
+                .map_err(rejected)
+        })
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for VerifiedToken<T> {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
+        let firebase_auth = get_firebase_auth(req);
+        verify_bearer(req, |token, options| {
+            firebase_auth.verify_token_with(token, options)
+        })
+    }
+}
+
+/// Requires a valid token in the `X-Firebase-AppCheck` header. Use `Option<AppCheckToken>` to
+/// accept requests without a valid token, e.g. while rolling out App Check.
+impl FromRequest for AppCheckToken {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut dev::Payload) -> Self::Future {
+        let app_check = req
+            .app_data::<web::Data<AppCheck>>()
+            .expect("must init AppCheck in Application Data to extract AppCheckToken");
+
+        let token = match req
+            .headers()
+            .get(APP_CHECK_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(v) => v,
+            None => return err(ErrorUnauthorized("Missing App Check Token")),
+        };
+
+        match app_check.verify(token) {
+            Err(e) => err(ErrorUnauthorized(format!(
+                "Failed to verify App Check Token {}",
+                e
+            ))),
+            Ok(token) => ok(token),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{
+    firebase_auth::get_app_check_configuration, AppCheckToken, JwkConfiguration, TokenProfile,
+    TokenVerifier,
+};
+
+/// Verifies Firebase App Check tokens, which attest that a request comes from a genuine
+/// instance of your app. Use together with the [`AppCheckToken`] extractor, or
+/// `Option<AppCheckToken>` to accept requests without a valid token while rolling out App
+/// Check. The audience is the project number, e.g. `123456789`.
+pub type AppCheck = TokenVerifier<AppCheckToken>;
+
+impl TokenProfile for AppCheckToken {
+    fn configuration(project_number: &str) -> JwkConfiguration {
+        get_app_check_configuration(project_number)
+    }
+}
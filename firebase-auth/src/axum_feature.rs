@@ -1,13 +1,21 @@
-use std::sync::Arc;
+use std::{convert::Infallible, sync::Arc};
 
 use axum::{
-    extract::{FromRef, FromRequestParts},
+    extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
     http::{self, request::Parts, StatusCode},
     response::{IntoResponse, Response},
 };
+use serde::de::DeserializeOwned;
+
 use tracing::debug;
 
-use crate::{FirebaseAuth, FirebaseUser};
+use crate::{
+    AppCheck, AppCheckToken, FirebaseAuth, FirebaseUser, GoogleOidcAuth, GoogleUser, IapAuth,
+    IapUser, JwtClaims, JwtVerifier, SingleUse, VerificationError, VerifiedToken, VerifyOptions,
+};
+
+const IAP_JWT_HEADER: &str = "x-goog-iap-jwt-assertion";
+const APP_CHECK_HEADER: &str = "x-firebase-appcheck";
 
 #[derive(Clone)]
 pub struct FirebaseAuthState {
@@ -31,6 +39,52 @@ fn get_bearer_token(header: &str) -> Option<String> {
     }
 }
 
+fn header_value<'a>(parts: &'a Parts, name: &str) -> &'a str {
+    parts
+        .headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+}
+
+/// The [`VerifyOptions`] added to the route with `.layer(Extension(options))`.
+fn verify_options(parts: &Parts) -> VerifyOptions {
+    parts
+        .extensions
+        .get::<VerifyOptions>()
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// The token of the `Authorization: Bearer` header.
+fn bearer_token(parts: &Parts) -> Option<String> {
+    get_bearer_token(header_value(parts, http::header::AUTHORIZATION.as_str()))
+}
+
+fn required_token(
+    token: Option<String>,
+    missing_msg: &str,
+) -> Result<String, UnauthorizedResponse> {
+    token.ok_or(UnauthorizedResponse {
+        msg: missing_msg.to_string(),
+    })
+}
+
+fn rejected(e: VerificationError) -> UnauthorizedResponse {
+    UnauthorizedResponse {
+        msg: format!("Failed to verify Token: {}", e),
+    }
+}
+
+fn verify_token<T>(
+    token: Option<String>,
+    missing_msg: &str,
+    verify: impl FnOnce(&str) -> Result<T, VerificationError>,
+) -> Result<T, UnauthorizedResponse> {
+    let token = required_token(token, missing_msg)?;
+    verify(&token).map_err(rejected)
+}
+
 impl<S> FromRequestParts<S> for FirebaseUser
 where
     FirebaseAuthState: FromRef<S>,
@@ -40,27 +94,156 @@ where
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let store = FirebaseAuthState::from_ref(state);
+        let options = verify_options(parts);
+        verify_token(bearer_token(parts), "Missing Bearer Token", |token| {
+            store.firebase_auth.verify_with(token, &options)
+        })
+    }
+}
+
+/// Requires a [`GoogleOidcAuth`] in the state, added next to [`FirebaseAuthState`] with
+/// `FromRef`.
+impl<S> FromRequestParts<S> for GoogleUser
+where
+    GoogleOidcAuth: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = UnauthorizedResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let google_auth = GoogleOidcAuth::from_ref(state);
+        let options = verify_options(parts);
+        verify_token(bearer_token(parts), "Missing Bearer Token", |token| {
+            google_auth.verify_with(token, &options)
+        })
+    }
+}
+
+/// Requires an [`IapAuth`] in the state, added next to [`FirebaseAuthState`] with `FromRef`.
+impl<S> FromRequestParts<S> for IapUser
+where
+    IapAuth: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = UnauthorizedResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let iap_auth = IapAuth::from_ref(state);
+        let assertion = header_value(parts, IAP_JWT_HEADER);
+        let assertion = (!assertion.is_empty()).then(|| assertion.to_string());
+        let options = verify_options(parts);
+        verify_token(assertion, "Missing IAP Jwt Assertion", |assertion| {
+            iap_auth.verify_with(assertion, &options)
+        })
+    }
+}
+
+impl<S, T> FromRequestParts<S> for SingleUse<T>
+where
+    FirebaseAuthState: FromRef<S>,
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = UnauthorizedResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let store = FirebaseAuthState::from_ref(state);
+        let bearer = required_token(bearer_token(parts), "Missing Bearer Token")?;
+        let options = verify_options(parts);
+        store
+            .firebase_auth
+            .verify_once_with(&bearer, &options)
+            .await
+            .map(SingleUse)
+            .map_err(rejected)
+    }
+}
+
+impl<S, T> FromRequestParts<S> for VerifiedToken<T>
+where
+    FirebaseAuthState: FromRef<S>,
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = UnauthorizedResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let store = FirebaseAuthState::from_ref(state);
+        let options = verify_options(parts);
+        verify_token(bearer_token(parts), "Missing Bearer Token", |token| {
+            store.firebase_auth.verify_token_with(token, &options)
+        })
+    }
+}
+
+/// Requires a [`JwtVerifier`] in the state, added next to [`FirebaseAuthState`] with
+/// `FromRef`.
+impl<S, T> FromRequestParts<S> for JwtClaims<T>
+where
+    JwtVerifier: FromRef<S>,
+    S: Send + Sync,
+    T: DeserializeOwned,
+{
+    type Rejection = UnauthorizedResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let verifier = JwtVerifier::from_ref(state);
+        let options = verify_options(parts);
+        verify_token(bearer_token(parts), "Missing Bearer Token", |token| {
+            verifier.verify_with(token, &options).map(JwtClaims)
+        })
+    }
+}
+
+fn verify_app_check(
+    app_check: &AppCheck,
+    parts: &Parts,
+) -> Result<AppCheckToken, UnauthorizedResponse> {
+    let token = header_value(parts, APP_CHECK_HEADER);
+    if token.is_empty() {
+        return Err(UnauthorizedResponse {
+            msg: "Missing App Check Token".to_string(),
+        });
+    }
+
+    app_check.verify(token).map_err(|e| UnauthorizedResponse {
+        msg: format!("Failed to verify App Check Token: {}", e),
+    })
+}
+
+/// Requires a valid token in the `X-Firebase-AppCheck` header. Add [`AppCheck`] to the state
+/// next to [`FirebaseAuthState`] with `FromRef`.
+impl<S> FromRequestParts<S> for AppCheckToken
+where
+    AppCheck: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = UnauthorizedResponse;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        verify_app_check(&AppCheck::from_ref(state), parts)
+    }
+}
+
+/// `Option<AppCheckToken>` accepts requests without a valid App Check token, e.g. while
+/// rolling out App Check.
+impl<S> OptionalFromRequestParts<S> for AppCheckToken
+where
+    AppCheck: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
 
-        let auth_header = parts
-            .headers
-            .get(http::header::AUTHORIZATION)
-            .and_then(|value| value.to_str().ok())
-            .unwrap_or("");
-
-        let bearer = get_bearer_token(auth_header).map_or(
-            Err(UnauthorizedResponse {
-                msg: "Missing Bearer Token".to_string(),
-            }),
-            Ok,
-        )?;
-
-        debug!("Got bearer token {}", bearer);
-
-        match store.firebase_auth.verify(&bearer) {
-            Err(e) => Err(UnauthorizedResponse {
-                msg: format!("Failed to verify Token: {}", e),
-            }),
-            Ok(current_user) => Ok(current_user),
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        match verify_app_check(&AppCheck::from_ref(state), parts) {
+            Ok(token) => Ok(Some(token)),
+            Err(e) => {
+                debug!("Accepting request without App Check: {}", e.msg);
+                Ok(None)
+            }
         }
     }
 }
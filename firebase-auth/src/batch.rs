@@ -0,0 +1,51 @@
+#[cfg(not(feature = "rayon"))]
+use std::{num::NonZeroUsize, panic, thread};
+
+/// Each thread verifies at least this many tokens, as spawning a thread costs about as much
+/// as verifying a few signatures.
+#[cfg(not(feature = "rayon"))]
+const MIN_CHUNK_SIZE: usize = 8;
+
+/// Apply `f` to all items on the rayon thread pool, keeping their order.
+#[cfg(feature = "rayon")]
+pub(crate) fn map_parallel<I, O, F>(items: &[I], f: F) -> Vec<O>
+where
+    I: Sync,
+    O: Send,
+    F: Fn(&I) -> O + Sync,
+{
+    use rayon::prelude::*;
+
+    items.par_iter().map(&f).collect()
+}
+
+/// Apply `f` to all items on scoped threads, one per available CPU, keeping their order. The
+/// calling thread handles the first chunk.
+#[cfg(not(feature = "rayon"))]
+pub(crate) fn map_parallel<I, O, F>(items: &[I], f: F) -> Vec<O>
+where
+    I: Sync,
+    O: Send,
+    F: Fn(&I) -> O + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = items.len().div_ceil(threads).max(MIN_CHUNK_SIZE);
+    if items.len() <= chunk_size {
+        return items.iter().map(f).collect();
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let mut chunks = items.chunks(chunk_size);
+        let first = chunks.next().unwrap_or_default();
+        let handles: Vec<_> = chunks
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+
+        let mut results: Vec<O> = first.iter().map(f).collect();
+        for handle in handles {
+            results.extend(handle.join().unwrap_or_else(|e| panic::resume_unwind(e)));
+        }
+        results
+    })
+}
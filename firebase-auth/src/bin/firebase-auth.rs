@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::Parser;
+use firebase_auth::{get_configuration, FirebaseAuth, TokenReport};
+use serde_json::Value;
+
+/// Decode a Firebase ID token and show which verification checks pass.
+#[derive(Parser)]
+#[command(name = "firebase-auth", version)]
+struct Args {
+    /// The Firebase project id the token must be issued for.
+    #[arg(long)]
+    project_id: String,
+
+    /// Verify against a JWKS document or `kid -> PEM certificate` map instead of fetching the
+    /// Google public keys.
+    #[arg(long)]
+    jwks_file: Option<PathBuf>,
+
+    /// The token, read from stdin when omitted or `-`.
+    token: Option<String>,
+}
+
+fn read_token(token: Option<String>) -> io::Result<String> {
+    match token {
+        Some(token) if token != "-" => Ok(token),
+        _ => {
+            let mut token = String::new();
+            io::stdin().read_to_string(&mut token)?;
+            Ok(token)
+        }
+    }
+}
+
+fn pretty(value: &Option<Value>) -> String {
+    match value {
+        Some(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+        None => "cannot decode".to_owned(),
+    }
+}
+
+fn print_report(report: &TokenReport) {
+    println!("Header:\n{}\n", pretty(&report.header));
+    println!("Claims:\n{}\n", pretty(&report.claims));
+    println!("Kid: {}\n", report.kid.as_deref().unwrap_or("none"));
+
+    println!("Checks:");
+    for check in &report.checks {
+        let status = if check.passed { "PASS" } else { "FAIL" };
+        println!("  [{}] {}: {}", status, check.name, check.detail);
+    }
+
+    match &report.result {
+        Ok(_) => println!("\nResult: valid"),
+        Err(e) => println!("\nResult: invalid ({})", e),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let token = match read_token(args.token) {
+        Ok(token) => token.trim().to_owned(),
+        Err(e) => {
+            eprintln!("Cannot read the token from stdin: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let firebase_auth = match &args.jwks_file {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))
+            .and_then(|document| {
+                FirebaseAuth::from_static_keys(&args.project_id, &document)
+                    .map_err(|e| format!("Cannot parse {}: {:?}", path.display(), e))
+            }),
+        None => FirebaseAuth::try_with_configuration_blocking(get_configuration(&args.project_id))
+            .map_err(|e| format!("Cannot fetch the public keys: {:?}", e)),
+    };
+    let firebase_auth = match firebase_auth {
+        Ok(firebase_auth) => firebase_auth,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = firebase_auth.inspect(&token);
+    print_report(&report);
+
+    if report.result.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
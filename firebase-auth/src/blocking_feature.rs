@@ -0,0 +1,36 @@
+use std::sync::Mutex;
+
+use crate::{KeyFetchResponse, PublicKeysError};
+
+/// Fetches the keys synchronously for [`FirebaseAuth::new_blocking`](crate::FirebaseAuth::new_blocking),
+/// refreshing lazily when a token is verified after the keys are due.
+pub(crate) struct BlockingRefresher {
+    client: reqwest::blocking::Client,
+    /// Held while refreshing, so that only one caller of `verify` fetches the keys.
+    pub(crate) refreshing: Mutex<()>,
+}
+
+impl BlockingRefresher {
+    pub(crate) fn new(client: reqwest::blocking::Client) -> Self {
+        BlockingRefresher {
+            client,
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn fetch(&self, url: &str) -> Result<KeyFetchResponse, PublicKeysError> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .map_err(PublicKeysError::CouldntFetchPublicKeys)?;
+
+        let head = KeyFetchResponse::from_head(response.status(), response.headers());
+        let body = response
+            .bytes()
+            .map_err(PublicKeysError::CouldntFetchPublicKeys)?
+            .to_vec();
+
+        Ok(KeyFetchResponse { body, ..head })
+    }
+}
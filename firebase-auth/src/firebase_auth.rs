@@ -1,79 +1,185 @@
 use base64::{prelude::BASE64_STANDARD_NO_PAD, Engine};
+use futures::{
+    channel::oneshot,
+    future::{AbortHandle, Abortable},
+};
 use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 use std::{
     env,
-    sync::{Arc, Mutex, RwLock},
-    time::Duration,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock, RwLock},
+    time::{Duration, Instant, SystemTime},
 };
-use tokio::{task::JoinHandle, time::sleep};
+use tokio::sync::watch;
 use tracing::*;
 
-use crate::structs::{JwkConfiguration, JwkKeys, KeyResponse, PublicKeysError};
+use crate::batch::map_parallel;
+#[cfg(feature = "blocking")]
+use crate::blocking_feature::BlockingRefresher;
+use crate::clock::unix_now;
+use crate::inspect::{inspect_token, unverified_claims};
+use crate::key_cache::{load_key_cache, store_key_cache};
+use crate::key_fetcher::{KeyFetchResponse, KeyFetcher, ReqwestKeyFetcher};
+use crate::refresh::{freshness_lifetime, BackoffPolicy, RefreshPolicy};
+use crate::replay::ReplayGuard;
+use crate::spawner::{Spawner, TokioSpawner};
+use crate::structs::{
+    JwkConfiguration, JwkKey, JwkKeys, KeyDocument, KeyResponse, KeyStatus, OpenIdConfiguration,
+    PublicKeysError, RefreshEvent, TokenReport, VerificationCacheStats, VerifiedToken,
+};
+use crate::trace::{record_verification, token_fingerprint};
+use crate::verification_cache::VerificationCache;
+use crate::verify_options::VerifyOptions;
+use crate::x509::rsa_key_from_certificate;
 
-const FALLBACK_TIMEOUT: Duration = Duration::from_secs(60);
 const JWK_URL: &str =
     "https://www.googleapis.com/service_accounts/v1/jwk/securetoken@system.gserviceaccount.com";
+const X509_URL: &str =
+    "https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com";
+const IAP_JWK_URL: &str = "https://www.gstatic.com/iap/verify/public_key-jwk";
+const GOOGLE_OIDC_JWK_URL: &str = "https://www.googleapis.com/oauth2/v3/certs";
+const APP_CHECK_JWK_URL: &str = "https://firebaseappcheck.googleapis.com/v1/jwks";
 
 pub fn get_configuration(project_id: &str) -> JwkConfiguration {
     JwkConfiguration {
         jwk_url: JWK_URL.to_owned(),
         audience: project_id.to_owned(),
-        issuer: format!("https://securetoken.google.com/{}", project_id),
+        issuers: vec![format!("https://securetoken.google.com/{}", project_id)],
+        algorithms: vec![Algorithm::RS256],
+        allow_emulator: true,
     }
 }
 
-fn parse_max_age_value(cache_control_value: &str) -> Result<Duration, PublicKeysError> {
-    let tokens: Vec<(&str, &str)> = cache_control_value
-        .split(',')
-        .map(|s| s.split('=').map(|ss| ss.trim()).collect::<Vec<&str>>())
-        .map(|ss| {
-            let key = ss.first().unwrap_or(&"");
-            let val = ss.get(1).unwrap_or(&"");
-            (*key, *val)
-        })
-        .collect();
-    match tokens
-        .iter()
-        .find(|(key, _)| key.to_lowercase() == *"max-age")
-    {
-        None => Err(PublicKeysError::NoMaxAgeSpecified),
-        Some((_, str_val)) => Ok(Duration::from_secs(
-            str_val
-                .parse()
-                .map_err(|_| PublicKeysError::NonNumericMaxAge)?,
-        )),
+/// Same as [`get_configuration`] but reads the keys from the `kid -> PEM certificate` map that
+/// the Firebase Admin SDKs use. Tokens signed by a key whose certificate is outside of its
+/// validity period are rejected.
+pub fn get_x509_configuration(project_id: &str) -> JwkConfiguration {
+    JwkConfiguration {
+        jwk_url: X509_URL.to_owned(),
+        ..get_configuration(project_id)
     }
 }
 
-async fn get_public_keys() -> Result<JwkKeys, PublicKeysError> {
-    let response = reqwest::get(JWK_URL)
-        .await
-        .map_err(PublicKeysError::CouldntFetchPublicKeys)?;
+/// Configuration for the `x-goog-iap-jwt-assertion` header set by Identity-Aware Proxy.
+/// The audience has the form `/projects/PROJECT_NUMBER/apps/PROJECT_ID` or
+/// `/projects/PROJECT_NUMBER/global/backendServices/SERVICE_ID`.
+pub fn get_iap_configuration(audience: &str) -> JwkConfiguration {
+    JwkConfiguration {
+        jwk_url: IAP_JWK_URL.to_owned(),
+        audience: audience.to_owned(),
+        issuers: vec!["https://cloud.google.com/iap".to_owned()],
+        algorithms: vec![Algorithm::ES256],
+        allow_emulator: false,
+    }
+}
 
-    let cache_control = match response.headers().get("Cache-Control") {
-        Some(header_value) => header_value.to_str(),
-        None => return Err(PublicKeysError::NoCacheControlHeader),
-    };
+/// Configuration for Google-signed OIDC tokens, e.g. Cloud Run service-to-service calls.
+/// The audience is usually the URL of the receiving service.
+pub fn get_google_oidc_configuration(audience: &str) -> JwkConfiguration {
+    JwkConfiguration {
+        jwk_url: GOOGLE_OIDC_JWK_URL.to_owned(),
+        audience: audience.to_owned(),
+        issuers: vec![
+            "https://accounts.google.com".to_owned(),
+            "accounts.google.com".to_owned(),
+        ],
+        algorithms: vec![Algorithm::RS256],
+        allow_emulator: false,
+    }
+}
 
-    let max_age = match cache_control {
-        Ok(v) => parse_max_age_value(v),
-        Err(_) => return Err(PublicKeysError::MaxAgeValueEmpty),
-    };
+/// Configuration for Firebase App Check tokens sent in the `X-Firebase-AppCheck` header, see
+/// [`AppCheck`](crate::AppCheck). App Check identifies projects by their number, not their id.
+pub fn get_app_check_configuration(project_number: &str) -> JwkConfiguration {
+    JwkConfiguration {
+        jwk_url: APP_CHECK_JWK_URL.to_owned(),
+        audience: format!("projects/{}", project_number),
+        issuers: vec![format!(
+            "https://firebaseappcheck.googleapis.com/{}",
+            project_number
+        )],
+        algorithms: vec![Algorithm::RS256],
+        allow_emulator: false,
+    }
+}
 
-    let public_keys = response
-        .json::<KeyResponse>()
-        .await
-        .map_err(|e| {
-            PublicKeysError::CannotParsePublicKey(e)
-        })?;
+/// Build a configuration from the `.well-known/openid-configuration` document of an OpenID
+/// Connect issuer. Symmetric algorithms advertised by the issuer are never accepted.
+pub async fn get_discovery_configuration(
+    issuer_url: &str,
+    audience: &str,
+    key_fetcher: &dyn KeyFetcher,
+) -> Result<JwkConfiguration, PublicKeysError> {
+    let issuer_url = issuer_url.trim_end_matches('/');
+    let discovery_url = format!("{}/.well-known/openid-configuration", issuer_url);
 
-    Ok(JwkKeys {
-        keys: public_keys.keys,
-        max_age: max_age.unwrap_or(FALLBACK_TIMEOUT),
+    let response = key_fetcher.fetch(&discovery_url).await?;
+    if !(200..300).contains(&response.status) {
+        return Err(PublicKeysError::UnexpectedStatus(response.status));
+    }
+    let discovery: OpenIdConfiguration =
+        serde_json::from_slice(&response.body).map_err(PublicKeysError::CannotParseDiscovery)?;
+
+    if discovery.issuer.trim_end_matches('/') != issuer_url {
+        return Err(PublicKeysError::DiscoveryIssuerMismatch);
+    }
+
+    let algorithms: Vec<Algorithm> = discovery
+        .id_token_signing_alg_values_supported
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|alg| Algorithm::from_str(alg).ok())
+        .filter(|alg| !matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))
+        .collect();
+
+    Ok(JwkConfiguration {
+        jwk_url: discovery.jwks_uri,
+        audience: audience.to_owned(),
+        issuers: vec![discovery.issuer],
+        algorithms: if algorithms.is_empty() {
+            vec![Algorithm::RS256]
+        } else {
+            algorithms
+        },
+        allow_emulator: false,
     })
 }
 
+async fn get_public_keys(
+    key_fetcher: &dyn KeyFetcher,
+    jwk_url: &str,
+) -> Result<JwkKeys, PublicKeysError> {
+    let response = key_fetcher.fetch(jwk_url).await?;
+    public_keys_from_response(response)
+}
+
+fn public_keys_from_response(response: KeyFetchResponse) -> Result<JwkKeys, PublicKeysError> {
+    if !(200..300).contains(&response.status) {
+        return Err(PublicKeysError::UnexpectedStatus(response.status));
+    }
+
+    let max_age = freshness_lifetime(&response, SystemTime::now());
+
+    let document = String::from_utf8_lossy(&response.body);
+    let keys = parse_key_document(&document)?;
+
+    Ok(JwkKeys { keys, max_age })
+}
+
+/// Parse a JWKS document or a `kid -> PEM certificate` map into public keys.
+pub fn parse_key_document(document: &str) -> Result<Vec<JwkKey>, PublicKeysError> {
+    match serde_json::from_str(document).map_err(PublicKeysError::CannotParsePublicKey)? {
+        KeyDocument::Jwks(KeyResponse { keys }) => Ok(keys),
+        KeyDocument::Certificates(certificates) => certificates
+            .iter()
+            .map(|(kid, certificate)| rsa_key_from_certificate(kid, certificate))
+            .collect(),
+    }
+}
+
 #[derive(Debug)]
 pub enum VerificationError {
     InvalidSignature,
@@ -82,6 +188,50 @@ pub enum VerificationError {
     NoKidHeader,
     NotfoundMatchKid,
     CannotDecodePublicKeys,
+    CertificateNotValid,
+    /// Refreshing the public keys failed for longer than
+    /// [`BackoffPolicy::max_staleness`](crate::BackoffPolicy::max_staleness).
+    KeysExpired,
+    /// The token was already used, see [`FirebaseAuth::verify_once`].
+    TokenReplayed,
+    /// The user signed in longer ago than
+    /// [`VerifyOptions::max_auth_age`](crate::VerifyOptions::max_auth_age) and needs to
+    /// reauthenticate.
+    StaleAuthentication,
+    /// A claim of [`VerifyOptions::required_claims`](crate::VerifyOptions::required_claims) is
+    /// missing.
+    MissingClaim,
+    /// The token was issued longer ago than
+    /// [`VerifyOptions::max_token_age`](crate::VerifyOptions::max_token_age).
+    TokenTooOld,
+    /// The user signed in with a provider not in
+    /// [`VerifyOptions::sign_in_providers`](crate::VerifyOptions::sign_in_providers).
+    SignInProviderNotAllowed,
+    /// The email of the user is not verified, see
+    /// [`VerifyOptions::require_email_verified`](crate::VerifyOptions::require_email_verified).
+    EmailNotVerified,
+}
+
+impl VerificationError {
+    /// The variant name, used as the outcome in traces and metrics.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            VerificationError::InvalidSignature => "InvalidSignature",
+            VerificationError::InvalidKeyAlgorithm => "InvalidKeyAlgorithm",
+            VerificationError::InvalidToken => "InvalidToken",
+            VerificationError::NoKidHeader => "NoKidHeader",
+            VerificationError::NotfoundMatchKid => "NotfoundMatchKid",
+            VerificationError::CannotDecodePublicKeys => "CannotDecodePublicKeys",
+            VerificationError::CertificateNotValid => "CertificateNotValid",
+            VerificationError::KeysExpired => "KeysExpired",
+            VerificationError::TokenReplayed => "TokenReplayed",
+            VerificationError::StaleAuthentication => "StaleAuthentication",
+            VerificationError::MissingClaim => "MissingClaim",
+            VerificationError::TokenTooOld => "TokenTooOld",
+            VerificationError::SignInProviderNotAllowed => "SignInProviderNotAllowed",
+            VerificationError::EmailNotVerified => "EmailNotVerified",
+        }
+    }
 }
 
 impl std::fmt::Display for VerificationError {
@@ -100,18 +250,61 @@ fn extract_claims_from_unsigned_token<T: DeserializeOwned>(token: &str) ->  Resu
     Ok(claims)
 }
 
-fn verify_id_token_with_project_id<T: DeserializeOwned>(
+fn decoding_key(public_key: &JwkKey, alg: Algorithm) -> Result<DecodingKey, VerificationError> {
+    if let Some(key_alg) = &public_key.alg {
+        if Algorithm::from_str(key_alg).ok() != Some(alg) {
+            return Err(VerificationError::InvalidKeyAlgorithm);
+        }
+    }
+
+    let crv = public_key.crv.as_deref();
+    let decoding_key = match (alg, public_key.kty.as_str(), crv) {
+        (
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512,
+            "RSA",
+            _,
+        ) => DecodingKey::from_rsa_components(
+            key_component(&public_key.n)?,
+            key_component(&public_key.e)?,
+        ),
+        (Algorithm::ES256, "EC", Some("P-256")) | (Algorithm::ES384, "EC", Some("P-384")) => {
+            DecodingKey::from_ec_components(
+                key_component(&public_key.x)?,
+                key_component(&public_key.y)?,
+            )
+        }
+        (Algorithm::EdDSA, "OKP", Some("Ed25519")) => {
+            DecodingKey::from_ed_components(key_component(&public_key.x)?)
+        }
+        _ => return Err(VerificationError::InvalidKeyAlgorithm),
+    };
+    decoding_key.map_err(|_| VerificationError::CannotDecodePublicKeys)
+}
+
+fn key_component(value: &Option<String>) -> Result<&str, VerificationError> {
+    value
+        .as_deref()
+        .ok_or(VerificationError::CannotDecodePublicKeys)
+}
+
+pub(crate) fn verify_id_token_with_project_id<T: DeserializeOwned>(
     config: &JwkConfiguration,
     public_keys: &JwkKeys,
     token: &str,
+    options: &VerifyOptions,
 ) -> Result<T, VerificationError> {
-    if env::var("FIREBASE_AUTH_EMULATOR_HOST").is_ok() {
+    if config.allow_emulator && env::var("FIREBASE_AUTH_EMULATOR_HOST").is_ok() {
         return extract_claims_from_unsigned_token(token);
     }
-    
+
     let header = decode_header(token).map_err(|_| VerificationError::InvalidSignature)?;
 
-    if header.alg != Algorithm::RS256 {
+    if !config.algorithms.contains(&header.alg) {
         return Err(VerificationError::InvalidKeyAlgorithm);
     }
 
@@ -125,12 +318,24 @@ fn verify_id_token_with_project_id<T: DeserializeOwned>(
         None => return Err(VerificationError::NotfoundMatchKid),
     };
 
-    let decoding_key = DecodingKey::from_rsa_components(&public_key.n, &public_key.e)
-        .map_err(|_| VerificationError::CannotDecodePublicKeys)?;
+    let now = unix_now() as i64;
+    if public_key.not_before.is_some_and(|t| now < t)
+        || public_key.not_after.is_some_and(|t| now > t)
+    {
+        return Err(VerificationError::CertificateNotValid);
+    }
 
-    let mut validation = Validation::new(Algorithm::RS256);
-    validation.set_audience(&[config.audience.to_owned()]);
-    validation.set_issuer(&[config.issuer.to_owned()]);
+    let decoding_key = decoding_key(public_key, header.alg)?;
+
+    let mut validation = Validation::new(header.alg);
+    let audiences: Vec<&str> = std::iter::once(config.audience.as_str())
+        .chain(options.audiences.iter().map(String::as_str))
+        .collect();
+    validation.set_audience(&audiences);
+    validation.set_issuer(&config.issuers);
+    if let Some(leeway) = options.leeway {
+        validation.leeway = leeway.as_secs();
+    }
 
     let user = decode::<T>(token, &decoding_key, &validation)
         .map_err(|_| VerificationError::InvalidToken)?
@@ -142,93 +347,746 @@ fn verify_id_token_with_project_id<T: DeserializeOwned>(
 struct JwkVerifier {
     keys: JwkKeys,
     config: JwkConfiguration,
+    max_staleness: Duration,
+    /// `None` when the keys never expire, e.g. static keys.
+    keys_expire_at: Option<Instant>,
+    loaded_at: Instant,
+    /// Incremented when the `kid`s change, invalidating the verification cache.
+    key_generation: u64,
 }
 
 impl JwkVerifier {
-    fn new(project_id: &str, keys: JwkKeys) -> JwkVerifier {
-        JwkVerifier {
+    fn new(config: JwkConfiguration, keys: JwkKeys, max_staleness: Duration) -> JwkVerifier {
+        let mut verifier = JwkVerifier {
             keys,
-            config: get_configuration(project_id),
+            config,
+            max_staleness,
+            keys_expire_at: None,
+            loaded_at: Instant::now(),
+            key_generation: 0,
+        };
+        verifier.keys_expire_at = verifier.hard_expiry();
+        verifier
+    }
+
+    fn hard_expiry(&self) -> Option<Instant> {
+        Instant::now()
+            .checked_add(self.keys.max_age)?
+            .checked_add(self.max_staleness)
+    }
+
+    fn verify<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+        cache: Option<&VerificationCache>,
+    ) -> Result<T, VerificationError> {
+        if self.keys_expire_at.is_some_and(|t| Instant::now() > t) {
+            return Err(VerificationError::KeysExpired);
+        }
+        // Cached claims passed the validation of the configuration only.
+        let cache = cache.filter(|_| options.leeway.is_none() && options.audiences.is_empty());
+        if cache.is_none() && !options.checks_claims() {
+            return verify_id_token_with_project_id(&self.config, &self.keys, token, options);
+        }
+
+        let cached = cache.and_then(|cache| cache.get(token, self.key_generation));
+        #[cfg(feature = "metrics")]
+        if cache.is_some() {
+            crate::metrics_feature::record_cache_lookup(&self.config.jwk_url, cached.is_some());
         }
+        let claims = match cached {
+            Some(claims) => claims,
+            None => {
+                let claims: Value =
+                    verify_id_token_with_project_id(&self.config, &self.keys, token, options)?;
+                if let Some(cache) = cache {
+                    cache.insert(token, &claims, self.key_generation);
+                }
+                claims
+            }
+        };
+        options.check_claims(&claims)?;
+        serde_json::from_value(claims).map_err(|_| VerificationError::InvalidToken)
     }
 
-    fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, VerificationError> {
-        verify_id_token_with_project_id(&self.config, &self.keys, token)
+    /// [`JwkVerifier::verify`] in a `verify_token` span below `parent`, recording the metrics
+    /// and the outcome of the verification.
+    fn verify_traced<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+        cache: Option<&VerificationCache>,
+        parent: &Span,
+    ) -> Result<T, VerificationError> {
+        let span = info_span!(
+            parent: parent,
+            "verify_token",
+            token = %token_fingerprint(token),
+            kid = field::Empty,
+            uid = field::Empty,
+            sign_in_provider = field::Empty,
+            outcome = field::Empty,
+        );
+        let _entered = span.enter();
+
+        #[cfg(feature = "metrics")]
+        let started = Instant::now();
+        let result = self.verify(token, options, cache);
+        #[cfg(feature = "metrics")]
+        crate::metrics_feature::record_verification(
+            &self.config.jwk_url,
+            result.as_ref().err(),
+            started.elapsed(),
+            self.loaded_at.elapsed(),
+        );
+
+        if !span.is_disabled() {
+            record_verification(&span, token, result.as_ref().err());
+        }
+        result
     }
 
     fn set_keys(&mut self, keys: JwkKeys) {
+        if kids(&self.keys) != kids(&keys) {
+            self.key_generation += 1;
+        }
         self.keys = keys;
+        self.keys_expire_at = self.hard_expiry();
+        self.loaded_at = Instant::now();
     }
 }
 
-/// Provide a service to automatically pull the new google public key based on the Cache-Control
-/// header.
-/// If there is an error during refreshing, automatically retry indefinitely every 10 seconds.
+/// Provide a service to automatically pull the new google public key based on the caching
+/// headers of the key response, see [`RefreshPolicy`].
+/// If there is an error during refreshing, automatically retry indefinitely with exponential
+/// backoff, see [`BackoffPolicy`].
+/// Clones share the keys and the refresh task, which runs until the last clone is dropped or
+/// [`FirebaseAuth::shutdown`] is called.
 #[derive(Clone)]
 pub struct FirebaseAuth {
     verifier: Arc<RwLock<JwkVerifier>>,
-    handler: Arc<Mutex<Box<JoinHandle<()>>>>,
+    refresher: Arc<KeyRefresher>,
+    task: Arc<RefreshTask>,
+    replay_guard: Arc<OnceLock<ReplayGuard>>,
+    cache: Option<Arc<VerificationCache>>,
+    #[cfg(feature = "blocking")]
+    blocking: Option<Arc<BlockingRefresher>>,
+}
+
+/// Build a [`FirebaseAuth`] with non-default settings, see [`FirebaseAuth::builder`].
+pub struct FirebaseAuthBuilder {
+    config: JwkConfiguration,
+    /// `None` for the default [`ReqwestKeyFetcher`].
+    key_fetcher: Option<Arc<dyn KeyFetcher>>,
+    key_cache_file: Option<PathBuf>,
+    refresh_policy: RefreshPolicy,
+    backoff_policy: BackoffPolicy,
+    spawner: Option<Arc<dyn Spawner>>,
+    #[cfg(feature = "blocking")]
+    blocking_client: Option<reqwest::blocking::Client>,
+}
+
+impl FirebaseAuthBuilder {
+    /// Use a custom [`KeyFetcher`] instead of the default [`ReqwestKeyFetcher`].
+    pub fn key_fetcher(mut self, key_fetcher: impl KeyFetcher + 'static) -> Self {
+        self.key_fetcher = Some(Arc::new(key_fetcher));
+        self
+    }
+
+    /// Fetch the keys of [`FirebaseAuthBuilder::build_blocking`] with a custom client, e.g.
+    /// with a proxy or timeouts, instead of the default `reqwest::blocking::Client`.
+    #[cfg(feature = "blocking")]
+    pub fn blocking_client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.blocking_client = Some(client);
+        self
+    }
+
+    /// Persist the keys to `path` after every refresh. On startup, keys in the file that are
+    /// still fresh are used right away instead of waiting for the network. A missing, corrupt
+    /// or stale file falls back to fetching the keys.
+    pub fn key_cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.key_cache_file = Some(path.into());
+        self
+    }
+
+    /// Control when the keys are refreshed relative to their freshness lifetime.
+    pub fn refresh_policy(mut self, refresh_policy: RefreshPolicy) -> Self {
+        self.refresh_policy = refresh_policy;
+        self
+    }
+
+    /// Control how failed refreshes are retried and how long the last good keys stay usable.
+    pub fn backoff_policy(mut self, backoff_policy: BackoffPolicy) -> Self {
+        self.backoff_policy = backoff_policy;
+        self
+    }
+
+    /// Run the background refresh with a custom [`Spawner`] instead of [`TokioSpawner`].
+    pub fn spawner(mut self, spawner: impl Spawner + 'static) -> Self {
+        self.spawner = Some(Arc::new(spawner));
+        self
+    }
+
+    /// Don't refresh the keys in the background. Call [`FirebaseAuth::refresh_keys`] instead,
+    /// starting at [`KeyStatus::next_refresh`].
+    pub fn manual_refresh(mut self) -> Self {
+        self.spawner = None;
+        self
+    }
+
+    /// Fetch the public keys and start refreshing them in the background.
+    pub async fn build(mut self) -> Result<FirebaseAuth, PublicKeysError> {
+        let key_fetcher = self
+            .key_fetcher
+            .take()
+            .unwrap_or_else(|| Arc::new(ReqwestKeyFetcher::new()));
+        let jwk_url = &self.config.jwk_url;
+
+        let jwk_keys = match self.cached_keys() {
+            Some(keys) => keys,
+            None => {
+                let keys = get_public_keys(key_fetcher.as_ref(), jwk_url).await?;
+                if let Some(path) = &self.key_cache_file {
+                    store_key_cache(path, jwk_url, &keys);
+                }
+                keys
+            }
+        };
+        let first_refresh = self.refresh_policy.refresh_delay(jwk_keys.max_age);
+        debug!("Next JWK keys refresh will be in {:?}", first_refresh);
+
+        let instance = FirebaseAuth::from_parts(
+            JwkVerifier::new(self.config, jwk_keys, self.backoff_policy.max_staleness),
+            Some(key_fetcher),
+            self.key_cache_file,
+            self.refresh_policy,
+            self.backoff_policy,
+            Some(first_refresh),
+        );
+        if let Some(spawner) = self.spawner {
+            instance.start_key_update(spawner, first_refresh);
+        }
+        Ok(instance)
+    }
+
+    /// Fetch the public keys with a blocking client, see [`FirebaseAuth::new_blocking`]. The
+    /// keys are refreshed on [`FirebaseAuth::verify`] following the refresh and backoff
+    /// policies, and persisted to the key cache file. No spawner is used. Fails with
+    /// [`PublicKeysError::UnsupportedKeyFetcher`] when a custom [`KeyFetcher`] was set, use
+    /// [`FirebaseAuthBuilder::blocking_client`] instead.
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(mut self) -> Result<FirebaseAuth, PublicKeysError> {
+        if self.key_fetcher.is_some() {
+            return Err(PublicKeysError::UnsupportedKeyFetcher);
+        }
+        let blocking = BlockingRefresher::new(self.blocking_client.take().unwrap_or_default());
+
+        let jwk_keys = match self.cached_keys() {
+            Some(keys) => keys,
+            None => {
+                let jwk_url = &self.config.jwk_url;
+                let keys = public_keys_from_response(blocking.fetch(jwk_url)?)?;
+                if let Some(path) = &self.key_cache_file {
+                    store_key_cache(path, jwk_url, &keys);
+                }
+                keys
+            }
+        };
+        let first_refresh = self.refresh_policy.refresh_delay(jwk_keys.max_age);
+
+        let mut instance = FirebaseAuth::from_parts(
+            JwkVerifier::new(self.config, jwk_keys, self.backoff_policy.max_staleness),
+            None,
+            self.key_cache_file,
+            self.refresh_policy,
+            self.backoff_policy,
+            Some(first_refresh),
+        );
+        instance.blocking = Some(Arc::new(blocking));
+        Ok(instance)
+    }
+
+    /// The keys in the key cache file, if it exists and they are still fresh.
+    fn cached_keys(&self) -> Option<JwkKeys> {
+        let path = self.key_cache_file.as_deref()?;
+        let keys = load_key_cache(path, &self.config.jwk_url)?;
+        debug!("Loaded JWK keys from cache");
+        Some(keys)
+    }
 }
 
-impl Drop for FirebaseAuth {
+fn kids(jwk_keys: &JwkKeys) -> Vec<String> {
+    jwk_keys.keys.iter().map(|key| key.kid.clone()).collect()
+}
+
+/// Refreshes the keys of a [`JwkVerifier`] and keeps track of the outcome.
+struct KeyRefresher {
+    verifier: Arc<RwLock<JwkVerifier>>,
+    /// `None` for static keys, which are never refreshed.
+    key_fetcher: Option<Arc<dyn KeyFetcher>>,
+    key_cache_file: Option<PathBuf>,
+    refresh_policy: RefreshPolicy,
+    backoff_policy: BackoffPolicy,
+    status: Mutex<KeyStatus>,
+    events: watch::Sender<RefreshEvent>,
+}
+
+impl KeyRefresher {
+    /// Fetch the keys once and return the delay until the next attempt.
+    async fn refresh(&self) -> Duration {
+        let Some(key_fetcher) = &self.key_fetcher else {
+            return Duration::MAX;
+        };
+        let jwk_url = self.jwk_url();
+        let result = get_public_keys(key_fetcher.as_ref(), &jwk_url).await;
+        self.apply(&jwk_url, result)
+    }
+
+    fn jwk_url(&self) -> String {
+        self.verifier.read().unwrap().config.jwk_url.clone()
+    }
+
+    /// Whether the scheduled refresh time has passed.
+    #[cfg(feature = "blocking")]
+    fn is_due(&self) -> bool {
+        let next_refresh = self.status.lock().unwrap().next_refresh;
+        next_refresh.is_some_and(|t| t <= SystemTime::now())
+    }
+
+    /// Use the outcome of a key fetch and return the delay until the next attempt.
+    fn apply(&self, jwk_url: &str, result: Result<JwkKeys, PublicKeysError>) -> Duration {
+        #[cfg(feature = "metrics")]
+        crate::metrics_feature::record_refresh(jwk_url, result.is_ok());
+
+        match result {
+            Ok(jwk_keys) => {
+                if let Some(path) = &self.key_cache_file {
+                    store_key_cache(path, jwk_url, &jwk_keys);
+                }
+                let delay = self.refresh_policy.refresh_delay(jwk_keys.max_age);
+                let kids = kids(&jwk_keys);
+                self.verifier.write().unwrap().set_keys(jwk_keys);
+                debug!("Updated JWK keys. Next refresh will be in {:?}", delay);
+
+                let mut status = self.status.lock().unwrap();
+                status.last_refresh = Some(SystemTime::now());
+                status.next_refresh = SystemTime::now().checked_add(delay);
+                status.consecutive_failures = 0;
+                status.last_error = None;
+                self.events.send_replace(RefreshEvent::Refreshed {
+                    kids,
+                    next_refresh: delay,
+                });
+                delay
+            }
+            Err(err) => {
+                let mut status = self.status.lock().unwrap();
+                status.consecutive_failures += 1;
+                let failures = status.consecutive_failures;
+                let delay = self.backoff_policy.retry_delay(failures);
+                warn!(
+                    "Error getting public jwk keys {:?}. Attempt {} failed, re-try in {:?}",
+                    err, failures, delay
+                );
+
+                let error = format!("{:?}", err);
+                status.next_refresh = SystemTime::now().checked_add(delay);
+                status.last_error = Some(error.clone());
+                self.events.send_replace(RefreshEvent::Failed {
+                    error,
+                    consecutive_failures: failures,
+                    retry_in: delay,
+                });
+                delay
+            }
+        }
+    }
+}
+
+/// The background refresh task, shared by all clones of a [`FirebaseAuth`].
+#[derive(Default)]
+struct RefreshTask {
+    handle: Mutex<Option<RefreshTaskHandle>>,
+}
+
+struct RefreshTaskHandle {
+    abort: AbortHandle,
+    /// Resolves once the task is dropped by the spawner.
+    stopped: oneshot::Receiver<()>,
+}
+
+impl Drop for RefreshTask {
     fn drop(&mut self) {
-        // Stop the update thread when the updater is destructed
-        let handler = self.handler.lock().unwrap();
-        handler.abort();
+        // Stop the update thread when the last clone of the updater is destructed
+        if let Some(handle) = self.handle.get_mut().unwrap().take() {
+            handle.abort.abort();
+        }
     }
 }
 
 impl FirebaseAuth {
     pub async fn new(project_id: &str) -> FirebaseAuth {
-        let jwk_keys: JwkKeys = match get_public_keys().await {
-            Ok(keys) => keys,
+        FirebaseAuth::with_configuration(get_configuration(project_id)).await
+    }
+
+    pub async fn with_configuration(config: JwkConfiguration) -> FirebaseAuth {
+        match FirebaseAuth::try_with_configuration(config).await {
+            Ok(instance) => instance,
             Err(e) => {
                 eprintln!("Error getting public jwk keys {:?}", e);
                 panic!("Unable to get public jwk keys! Cannot verify user tokens! Shutting down...")
             }
+        }
+    }
+
+    pub async fn try_with_configuration(
+        config: JwkConfiguration,
+    ) -> Result<FirebaseAuth, PublicKeysError> {
+        FirebaseAuth::builder(config).build().await
+    }
+
+    /// Verify Firebase tokens against a fixed key set, without network access or a background
+    /// refresh task. `document` is either a JWKS document or a `kid -> PEM certificate` map.
+    /// Useful for hermetic tests and air-gapped deployments.
+    pub fn from_static_keys(
+        project_id: &str,
+        document: &str,
+    ) -> Result<FirebaseAuth, PublicKeysError> {
+        let keys = parse_key_document(document)?;
+        Ok(FirebaseAuth::with_static_keys(
+            get_configuration(project_id),
+            keys,
+        ))
+    }
+
+    /// Verify tokens against a fixed key set which is never refreshed, not even by
+    /// [`FirebaseAuth::refresh_keys`].
+    pub fn with_static_keys(config: JwkConfiguration, keys: Vec<JwkKey>) -> FirebaseAuth {
+        let jwk_keys = JwkKeys {
+            keys,
+            max_age: Duration::MAX,
         };
-        let verifier = Arc::new(RwLock::new(JwkVerifier::new(project_id, jwk_keys)));
 
-        let mut instance = FirebaseAuth {
-            verifier,
-            handler: Arc::new(Mutex::new(Box::new(tokio::spawn(async {})))),
+        FirebaseAuth::from_parts(
+            JwkVerifier::new(config, jwk_keys, Duration::MAX),
+            None,
+            None,
+            RefreshPolicy::default(),
+            BackoffPolicy::default(),
+            None,
+        )
+    }
+
+    pub fn builder(config: JwkConfiguration) -> FirebaseAuthBuilder {
+        FirebaseAuthBuilder {
+            config,
+            key_fetcher: None,
+            key_cache_file: None,
+            refresh_policy: RefreshPolicy::default(),
+            backoff_policy: BackoffPolicy::default(),
+            spawner: Some(Arc::new(TokioSpawner)),
+            #[cfg(feature = "blocking")]
+            blocking_client: None,
+        }
+    }
+
+    fn from_parts(
+        verifier: JwkVerifier,
+        key_fetcher: Option<Arc<dyn KeyFetcher>>,
+        key_cache_file: Option<PathBuf>,
+        refresh_policy: RefreshPolicy,
+        backoff_policy: BackoffPolicy,
+        first_refresh: Option<Duration>,
+    ) -> FirebaseAuth {
+        let kids = kids(&verifier.keys);
+        let status = KeyStatus {
+            last_refresh: Some(SystemTime::now()),
+            next_refresh: first_refresh.and_then(|delay| SystemTime::now().checked_add(delay)),
+            consecutive_failures: 0,
+            last_error: None,
+            kids: kids.clone(),
+            refresh_task_running: false,
         };
+        let verifier = Arc::new(RwLock::new(verifier));
 
-        instance.start_key_update();
-        instance
+        FirebaseAuth {
+            verifier: Arc::clone(&verifier),
+            refresher: Arc::new(KeyRefresher {
+                verifier,
+                key_fetcher,
+                key_cache_file,
+                refresh_policy,
+                backoff_policy,
+                status: Mutex::new(status),
+                events: watch::Sender::new(RefreshEvent::Loaded { kids }),
+            }),
+            task: Arc::default(),
+            replay_guard: Arc::default(),
+            cache: None,
+            #[cfg(feature = "blocking")]
+            blocking: None,
+        }
     }
 
+    /// Verify the token and decode its claims. Runs inside a `verify_token` span which records
+    /// a fingerprint of the token, never the token itself.
     pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, VerificationError> {
+        self.verify_with(token, &VerifyOptions::default())
+    }
+
+    /// Verify the token like [`FirebaseAuth::verify`] with a per-request policy, e.g. to require
+    /// a recent login on some routes while sharing one key cache.
+    pub fn verify_with<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+    ) -> Result<T, VerificationError> {
+        #[cfg(feature = "blocking")]
+        self.refresh_blocking_if_due();
+
+        let verifier = self.verifier.read().unwrap();
+        verifier.verify_traced(token, options, self.cache.as_deref(), &Span::current())
+    }
+
+    /// Verify all tokens in parallel against one snapshot of the keys, e.g. to re-authenticate
+    /// many connections at once. The results are in the order of `tokens`. This blocks the
+    /// calling thread until all tokens are verified, so call it from
+    /// `tokio::task::spawn_blocking` for large batches. With the `rayon` feature the tokens are
+    /// verified on the rayon thread pool.
+    pub fn verify_many<T: DeserializeOwned + Send>(
+        &self,
+        tokens: &[&str],
+    ) -> Vec<Result<T, VerificationError>> {
+        self.verify_many_with(tokens, &VerifyOptions::default())
+    }
+
+    /// Verify all tokens like [`FirebaseAuth::verify_with`], see [`FirebaseAuth::verify_many`].
+    pub fn verify_many_with<T: DeserializeOwned + Send>(
+        &self,
+        tokens: &[&str],
+        options: &VerifyOptions,
+    ) -> Vec<Result<T, VerificationError>> {
+        let span = info_span!(
+            "verify_tokens",
+            count = tokens.len(),
+            rejected = field::Empty
+        );
+        let _entered = span.enter();
+
+        #[cfg(feature = "blocking")]
+        self.refresh_blocking_if_due();
+
         let verifier = self.verifier.read().unwrap();
-        verifier.verify(token)
+        let verifier = &*verifier;
+        let cache = self.cache.as_deref();
+
+        // The workers inherit neither the current span nor a scoped subscriber of the caller.
+        let dispatch = dispatcher::get_default(Dispatch::clone);
+        let results = map_parallel(tokens, |token| {
+            dispatcher::with_default(&dispatch, || {
+                verifier.verify_traced(token, options, cache, &span)
+            })
+        });
+
+        span.record(
+            "rejected",
+            results.iter().filter(|result| result.is_err()).count(),
+        );
+        results
+    }
+
+    /// Verify the token like [`FirebaseAuth::verify`] and return its claims together with the
+    /// `kid` and algorithm it was signed with and its expiry.
+    pub fn verify_token<T: DeserializeOwned>(
+        &self,
+        token: &str,
+    ) -> Result<VerifiedToken<T>, VerificationError> {
+        self.verify_token_with(token, &VerifyOptions::default())
+    }
+
+    /// Verify the token like [`FirebaseAuth::verify_with`], see
+    /// [`FirebaseAuth::verify_token`].
+    pub fn verify_token_with<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+    ) -> Result<VerifiedToken<T>, VerificationError> {
+        let claims = self.verify_with(token, options)?;
+        let header = decode_header(token).map_err(|_| VerificationError::InvalidSignature)?;
+        let expires_at = unverified_claims(token)
+            .and_then(|claims| claims["exp"].as_u64())
+            .ok_or(VerificationError::InvalidToken)?;
+
+        Ok(VerifiedToken {
+            claims,
+            kid: header.kid.unwrap_or_default(),
+            algorithm: header.alg,
+            token: token.to_owned(),
+            expires_at,
+        })
+    }
+
+    /// Remember the claims of up to `capacity` verified tokens, so that clients sending the
+    /// same token on every request skip the signature verification. Entries expire with the
+    /// token or when the `kid`s of the keys change. Per-request checks of [`VerifyOptions`]
+    /// still run on every request, except that a custom `leeway` or `audiences` bypass the
+    /// cache.
+    pub fn with_verification_cache(mut self, capacity: usize) -> Self {
+        self.cache = Some(Arc::new(VerificationCache::new(capacity)));
+        self
+    }
+
+    /// The hit and miss counters of the verification cache, `None` without
+    /// [`FirebaseAuth::with_verification_cache`].
+    pub fn cache_stats(&self) -> Option<VerificationCacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Use `replay_guard` for [`FirebaseAuth::verify_once`] instead of an in-memory
+    /// [`ReplayGuard::default`].
+    pub fn with_replay_guard(mut self, replay_guard: ReplayGuard) -> Self {
+        self.replay_guard = Arc::new(OnceLock::from(replay_guard));
+        self
+    }
+
+    /// Verify the token and accept it only once within its lifetime, failing with
+    /// [`VerificationError::TokenReplayed`] afterwards. Use it for sensitive operations like
+    /// password changes or payment confirmations.
+    pub async fn verify_once<T: DeserializeOwned>(
+        &self,
+        token: &str,
+    ) -> Result<T, VerificationError> {
+        self.verify_once_with(token, &VerifyOptions::default())
+            .await
+    }
+
+    /// Verify the token once with a per-request policy, see [`FirebaseAuth::verify_with`].
+    pub async fn verify_once_with<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+    ) -> Result<T, VerificationError> {
+        let claims: Value = self.verify_with(token, options)?;
+        let exp = claims["exp"]
+            .as_u64()
+            .ok_or(VerificationError::InvalidToken)?;
+
+        self.replay_guard
+            .get_or_init(ReplayGuard::default)
+            .check(token, exp)
+            .await?;
+        serde_json::from_value(claims).map_err(|_| VerificationError::InvalidToken)
+    }
+
+    /// Run each verification check separately and report which ones pass, e.g. to debug why a
+    /// token is rejected.
+    pub fn inspect(&self, token: &str) -> TokenReport {
+        let verifier = self.verifier.read().unwrap();
+        inspect_token(&verifier.config, &verifier.keys, token)
+    }
+
+    /// The state of the key refresh, e.g. for a health check endpoint.
+    pub fn status(&self) -> KeyStatus {
+        let mut status = self.refresher.status.lock().unwrap().clone();
+        status.kids = kids(&self.verifier.read().unwrap().keys);
+        // The task drops the sender when it ends, including when it panics.
+        status.refresh_task_running = self
+            .task
+            .handle
+            .lock()
+            .unwrap()
+            .as_mut()
+            .is_some_and(|handle| matches!(handle.stopped.try_recv(), Ok(None)));
+        status
+    }
+
+    /// Subscribe to the outcome of every key refresh. The receiver starts with the most
+    /// recent event.
+    pub fn subscribe(&self) -> watch::Receiver<RefreshEvent> {
+        self.refresher.events.subscribe()
+    }
+
+    /// Fetch the keys once, e.g. when driving the refresh yourself after
+    /// [`FirebaseAuthBuilder::manual_refresh`], and return the delay until the next refresh.
+    /// Failures are retried with a shorter delay following the [`BackoffPolicy`]. Static keys
+    /// are kept as they are, returning `Duration::MAX`.
+    pub async fn refresh_keys(&self) -> Duration {
+        self.refresher.refresh().await
     }
 
-    fn start_key_update(&mut self) {
-        let verifier_ref = Arc::clone(&self.verifier);
+    fn start_key_update(&self, spawner: Arc<dyn Spawner>, first_refresh: Duration) {
+        let refresher = Arc::clone(&self.refresher);
+        let timer = Arc::clone(&spawner);
+        let (abort, registration) = AbortHandle::new_pair();
+        let (stopped_sender, stopped) = oneshot::channel::<()>();
 
-        let task = tokio::spawn(async move {
+        let task = async move {
+            let _stopped_sender = stopped_sender;
+            timer.sleep(first_refresh).await;
             loop {
-                let delay = match get_public_keys().await {
-                    Ok(jwk_keys) => {
-                        let mut verifier = verifier_ref.write().unwrap();
-                        verifier.set_keys(jwk_keys.clone());
-                        debug!(
-                            "Updated JWK keys. Next refresh will be in {:?}",
-                            jwk_keys.max_age
-                        );
-                        jwk_keys.max_age
-                    }
-                    Err(err) => {
-                        warn!("Error getting public jwk keys {:?}", err);
-                        warn!("Re-try getting public keys in 10 seconds");
-                        Duration::from_secs(10)
-                    }
-                };
-                sleep(delay).await;
+                let delay = refresher.refresh().await;
+                timer.sleep(delay).await;
             }
-        });
+        };
+        spawner.spawn(Box::pin(async move {
+            let _ = Abortable::new(task, registration).await;
+        }));
+
+        let mut handle = self.task.handle.lock().unwrap();
+        *handle = Some(RefreshTaskHandle { abort, stopped });
+    }
+
+    /// Stop refreshing the keys for this instance and all its clones. The current keys stay in
+    /// use until they expire. Resolves once the refresh task has stopped.
+    pub async fn shutdown(&self) {
+        let handle = self.task.handle.lock().unwrap().take();
+        if let Some(handle) = handle {
+            handle.abort.abort();
+            let _ = handle.stopped.await;
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl FirebaseAuth {
+    /// Verify Firebase tokens without an async runtime. The keys are fetched with a blocking
+    /// client, and refreshed on [`FirebaseAuth::verify`] once they are due instead of in the
+    /// background. Must not be called from within an async runtime.
+    pub fn new_blocking(project_id: &str) -> FirebaseAuth {
+        match FirebaseAuth::try_with_configuration_blocking(get_configuration(project_id)) {
+            Ok(instance) => instance,
+            Err(e) => {
+                eprintln!("Error getting public jwk keys {:?}", e);
+                panic!("Unable to get public jwk keys! Cannot verify user tokens! Shutting down...")
+            }
+        }
+    }
+
+    /// See [`FirebaseAuth::new_blocking`].
+    pub fn try_with_configuration_blocking(
+        config: JwkConfiguration,
+    ) -> Result<FirebaseAuth, PublicKeysError> {
+        FirebaseAuth::builder(config).build_blocking()
+    }
+
+    fn refresh_blocking_if_due(&self) {
+        let Some(blocking) = &self.blocking else {
+            return;
+        };
+        if !self.refresher.is_due() {
+            return;
+        }
+        // Another caller is already refreshing, keep using the current keys meanwhile.
+        let Ok(_refreshing) = blocking.refreshing.try_lock() else {
+            return;
+        };
+        if !self.refresher.is_due() {
+            return;
+        }
 
-        let mut handler = self.handler.lock().unwrap();
-        *handler = Box::new(task);
+        let jwk_url = self.refresher.jwk_url();
+        let result = blocking.fetch(&jwk_url).and_then(public_keys_from_response);
+        self.refresher.apply(&jwk_url, result);
     }
 }
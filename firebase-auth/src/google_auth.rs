@@ -0,0 +1,27 @@
+use crate::{
+    firebase_auth::{get_google_oidc_configuration, get_iap_configuration},
+    GoogleUser, IapUser, JwkConfiguration, TokenProfile, TokenVerifier,
+};
+
+/// Verifies the `x-goog-iap-jwt-assertion` header set by Google Cloud Identity-Aware Proxy.
+/// Use together with the [`IapUser`] extractor. The audience has the form
+/// `/projects/PROJECT_NUMBER/apps/PROJECT_ID` or
+/// `/projects/PROJECT_NUMBER/global/backendServices/SERVICE_ID`.
+pub type IapAuth = TokenVerifier<IapUser>;
+
+/// Verifies Google-signed OIDC tokens in the `Authorization: Bearer` header, e.g. Cloud Run
+/// service-to-service calls. Use together with the [`GoogleUser`] extractor. The audience is
+/// usually the URL of the receiving service.
+pub type GoogleOidcAuth = TokenVerifier<GoogleUser>;
+
+impl TokenProfile for IapUser {
+    fn configuration(audience: &str) -> JwkConfiguration {
+        get_iap_configuration(audience)
+    }
+}
+
+impl TokenProfile for GoogleUser {
+    fn configuration(audience: &str) -> JwkConfiguration {
+        get_google_oidc_configuration(audience)
+    }
+}
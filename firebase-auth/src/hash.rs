@@ -0,0 +1,15 @@
+use sha2::{Digest, Sha256};
+
+/// The SHA-256 hash of `token`.
+pub(crate) fn sha256(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+/// The first `len` bytes of the SHA-256 hash of `token` in lowercase hex.
+pub(crate) fn sha256_hex(token: &str, len: usize) -> String {
+    sha256(token)
+        .iter()
+        .take(len)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,121 @@
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode_header, Validation};
+use serde_json::Value;
+
+use crate::clock::unix_now;
+use crate::firebase_auth::verify_id_token_with_project_id;
+use crate::structs::{JwkConfiguration, JwkKeys, TokenCheck, TokenReport};
+use crate::VerifyOptions;
+
+fn check(name: &'static str, passed: bool, detail: impl Into<String>) -> TokenCheck {
+    TokenCheck {
+        name,
+        passed,
+        detail: detail.into(),
+    }
+}
+
+/// Decode the payload of a token without verifying it.
+pub(crate) fn unverified_claims(token: &str) -> Option<Value> {
+    let payload = BASE64_URL_SAFE_NO_PAD
+        .decode(token.split('.').nth(1)?)
+        .ok()?;
+    serde_json::from_slice(&payload).ok()
+}
+
+fn claim_checks(config: &JwkConfiguration, claims: &Value, now: i64) -> Vec<TokenCheck> {
+    // The clock skew that the verification tolerates for `exp`.
+    let leeway = Validation::default().leeway as i64;
+    let iss = claims["iss"].as_str().unwrap_or_default();
+    let audiences: Vec<&str> = match &claims["aud"] {
+        Value::String(aud) => vec![aud.as_str()],
+        Value::Array(auds) => auds.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    let exp = claims["exp"].as_i64();
+
+    vec![
+        check(
+            "issuer",
+            config.issuers.iter().any(|issuer| issuer == iss),
+            format!("got {:?}, expected one of {:?}", iss, config.issuers),
+        ),
+        check(
+            "audience",
+            audiences.contains(&config.audience.as_str()),
+            format!("got {:?}, expected {:?}", audiences, config.audience),
+        ),
+        check(
+            "expiry",
+            exp.is_some_and(|exp| exp >= now - leeway),
+            match exp {
+                Some(exp) => format!("exp {} vs now {} with {}s leeway", exp, now, leeway),
+                None => "no exp claim".to_owned(),
+            },
+        ),
+    ]
+}
+
+/// Run the verification steps one by one, then the full verification of
+/// [`verify_id_token_with_project_id`].
+pub(crate) fn inspect_token(config: &JwkConfiguration, keys: &JwkKeys, token: &str) -> TokenReport {
+    let now = unix_now() as i64;
+    let mut checks = vec![];
+
+    let header = decode_header(token);
+    checks.push(match &header {
+        Ok(_) => check("header", true, "decoded"),
+        Err(e) => check("header", false, e.to_string()),
+    });
+    let header = header.ok();
+
+    let kid = header.as_ref().and_then(|header| header.kid.clone());
+    if let Some(header) = &header {
+        checks.push(check(
+            "algorithm",
+            config.algorithms.contains(&header.alg),
+            format!("{:?}, allowed {:?}", header.alg, config.algorithms),
+        ));
+
+        let key = kid
+            .as_ref()
+            .and_then(|kid| keys.keys.iter().find(|key| &key.kid == kid));
+        checks.push(match (&kid, key) {
+            (None, _) => check("kid", false, "no kid header"),
+            (Some(kid), None) => check("kid", false, format!("no key with kid {:?}", kid)),
+            (Some(kid), Some(_)) => check("kid", true, format!("matched {:?}", kid)),
+        });
+
+        if let Some(key) = key.filter(|key| key.not_before.is_some() || key.not_after.is_some()) {
+            let valid =
+                key.not_before.is_none_or(|t| now >= t) && key.not_after.is_none_or(|t| now <= t);
+            checks.push(check(
+                "certificate",
+                valid,
+                format!("valid from {:?} to {:?}", key.not_before, key.not_after),
+            ));
+        }
+    }
+
+    let claims = unverified_claims(token);
+    match &claims {
+        Some(claims) => checks.extend(claim_checks(config, claims, now)),
+        None => checks.push(check("claims", false, "cannot decode the payload")),
+    }
+
+    let result =
+        verify_id_token_with_project_id::<Value>(config, keys, token, &VerifyOptions::default())
+            .map(|_| ());
+    checks.push(match &result {
+        Ok(_) => check("verification", true, "verified"),
+        Err(e) => check("verification", false, e.to_string()),
+    });
+
+    TokenReport {
+        header: header.and_then(|header| serde_json::to_value(header).ok()),
+        claims,
+        kid,
+        checks,
+        result,
+    }
+}
@@ -0,0 +1,53 @@
+use serde::de::DeserializeOwned;
+
+use crate::{
+    firebase_auth::get_discovery_configuration, FirebaseAuth, PublicKeysError, ReqwestKeyFetcher,
+    TokenVerifier, VerificationError, VerifiedToken, VerifyOptions,
+};
+
+/// The claims of a [`JwtVerifier`], chosen by the caller of every verification.
+pub enum AnyClaims {}
+
+/// Verifies tokens of any OpenID Connect issuer, e.g. Auth0. A separate type from
+/// [`FirebaseAuth`], so one app can accept Firebase ID tokens on some routes and tokens of
+/// another issuer on others. Use together with the [`JwtClaims`](crate::JwtClaims) extractor.
+pub type JwtVerifier = TokenVerifier<AnyClaims>;
+
+impl TokenVerifier<AnyClaims> {
+    /// Configure the verifier from the `jwks_uri` of the discovery document of the issuer.
+    pub async fn from_discovery(
+        issuer_url: &str,
+        audience: &str,
+    ) -> Result<JwtVerifier, PublicKeysError> {
+        let key_fetcher = ReqwestKeyFetcher::new();
+        let config = get_discovery_configuration(issuer_url, audience, &key_fetcher).await?;
+        let firebase_auth = FirebaseAuth::builder(config)
+            .key_fetcher(key_fetcher)
+            .build()
+            .await?;
+        Ok(JwtVerifier::from_firebase_auth(firebase_auth))
+    }
+
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, VerificationError> {
+        self.firebase_auth.verify(token)
+    }
+
+    /// Verify the token with a per-request policy, see [`FirebaseAuth::verify_with`].
+    pub fn verify_with<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+    ) -> Result<T, VerificationError> {
+        self.firebase_auth.verify_with(token, options)
+    }
+
+    /// Verify the token and return the details of its signing key, see
+    /// [`FirebaseAuth::verify_token`].
+    pub fn verify_token_with<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+    ) -> Result<VerifiedToken<T>, VerificationError> {
+        self.firebase_auth.verify_token_with(token, options)
+    }
+}
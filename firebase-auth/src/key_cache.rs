@@ -0,0 +1,62 @@
+use std::{fs, path::Path, time::Duration};
+
+use tracing::*;
+
+use crate::clock::unix_now;
+use crate::structs::{JwkKeys, KeyCacheFile};
+
+/// Load the keys written by [`store_key_cache`]. Returns `None` when the file is missing,
+/// corrupt, written for another key url or already expired.
+pub fn load_key_cache(path: &Path, jwk_url: &str) -> Option<JwkKeys> {
+    let content = match fs::read(path) {
+        Ok(v) => v,
+        Err(e) => {
+            debug!("No usable key cache at {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    let cache: KeyCacheFile = match serde_json::from_slice(&content) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Ignoring corrupt key cache at {:?}: {}", path, e);
+            return None;
+        }
+    };
+
+    if cache.jwk_url != jwk_url {
+        debug!("Ignoring key cache at {:?} for {}", path, cache.jwk_url);
+        return None;
+    }
+
+    let now = unix_now();
+    if cache.expires_at <= now {
+        debug!("Ignoring stale key cache at {:?}", path);
+        return None;
+    }
+
+    Some(JwkKeys {
+        keys: cache.keys,
+        max_age: Duration::from_secs(cache.expires_at - now),
+    })
+}
+
+/// Write the keys and their expiry next to `path` and move the file in place, so that a
+/// crash never leaves a half written cache behind.
+pub fn store_key_cache(path: &Path, jwk_url: &str, jwk_keys: &JwkKeys) {
+    let cache = KeyCacheFile {
+        jwk_url: jwk_url.to_owned(),
+        expires_at: unix_now().saturating_add(jwk_keys.max_age.as_secs()),
+        keys: jwk_keys.keys.clone(),
+    };
+
+    let tmp_path = path.with_extension("tmp");
+    let result = serde_json::to_vec(&cache)
+        .map_err(std::io::Error::from)
+        .and_then(|content| fs::write(&tmp_path, content))
+        .and_then(|_| fs::rename(&tmp_path, path));
+
+    if let Err(e) = result {
+        warn!("Error writing key cache to {:?}: {}", path, e);
+    }
+}
@@ -0,0 +1,93 @@
+use futures::future::BoxFuture;
+use reqwest::{header::HeaderMap, StatusCode};
+
+use crate::PublicKeysError;
+
+/// The raw HTTP response of a key set or discovery document request.
+#[derive(Debug, Clone)]
+pub struct KeyFetchResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl KeyFetchResponse {
+    /// Get the first header value with the given name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The status and headers of a `reqwest` response, with an empty body. Headers with values
+    /// that are not visible ASCII are skipped.
+    pub(crate) fn from_head(status: StatusCode, headers: &HeaderMap) -> Self {
+        let headers = headers
+            .iter()
+            .filter_map(|(key, value)| {
+                let value = value.to_str().ok()?;
+                Some((key.as_str().to_owned(), value.to_owned()))
+            })
+            .collect();
+
+        KeyFetchResponse {
+            status: status.as_u16(),
+            headers,
+            body: Vec::new(),
+        }
+    }
+}
+
+/// Fetch the public keys and discovery documents over HTTP. Implement it to bring your own
+/// client, e.g. with proxy settings, custom root certificates or timeouts, or to serve keys
+/// from memory in tests.
+pub trait KeyFetcher: Send + Sync {
+    fn fetch<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<KeyFetchResponse, PublicKeysError>>;
+}
+
+/// The default [`KeyFetcher`], reusing one `reqwest` client for every refresh.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestKeyFetcher {
+    client: reqwest::Client,
+}
+
+impl ReqwestKeyFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl From<reqwest::Client> for ReqwestKeyFetcher {
+    fn from(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl KeyFetcher for ReqwestKeyFetcher {
+    fn fetch<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<KeyFetchResponse, PublicKeysError>> {
+        Box::pin(async move {
+            let response = self
+                .client
+                .get(url)
+                .send()
+                .await
+                .map_err(PublicKeysError::CouldntFetchPublicKeys)?;
+
+            let head = KeyFetchResponse::from_head(response.status(), response.headers());
+            let body = response
+                .bytes()
+                .await
+                .map_err(PublicKeysError::CouldntFetchPublicKeys)?
+                .to_vec();
+
+            Ok(KeyFetchResponse { body, ..head })
+        })
+    }
+}
@@ -5,11 +5,17 @@
 //! - [Axum](https://github.com/tokio-rs/axum)
 //! - [Actix](https://github.com/actix/actix-web)
 //!
+//! Besides Firebase ID tokens, [`IapAuth`] and [`GoogleOidcAuth`] verify Google Cloud
+//! Identity-Aware Proxy assertions and Google-signed OIDC tokens, extracted with [`IapUser`] and
+//! [`GoogleUser`]. Other OpenID Connect issuers are supported through
+//! [`JwtVerifier::from_discovery`], extracted with [`JwtClaims`]. [`AppCheck`] verifies Firebase App Check tokens, extracted
+//! with [`AppCheckToken`].
+//!
 //! ## Example:
 //!
 //! ### Actix
 //!
-//! ```rust
+//! ```rust,no_run
 //! use actix_web::{get, middleware::Logger, web::Data, App, HttpServer, Responder};
 //! use firebase_auth::{FirebaseAuth, FirebaseUser};
 //!
@@ -45,7 +51,7 @@
 //!
 //! ### Axum
 //!
-//! ```rust
+//! ```rust,no_run
 //! use axum::{routing::get, Router};
 //! use firebase_auth::{FirebaseAuth, FirebaseAuthState, FirebaseUser};
 //!
@@ -65,7 +71,7 @@
 //!     let app = Router::new()
 //!         .route("/hello", get(greet))
 //!         .route("/", get(public))
-//!         .with_state(FirebaseAuthState { firebase_auth });
+//!         .with_state(FirebaseAuthState::new(firebase_auth));
 //!
 //!
 //!     let addr = "127.0.0.1:8080";
@@ -77,11 +83,67 @@
 //!
 //!Visit [README.md](https://github.com/trchopan/firebase-auth/) for more details.
 
+mod app_check;
+pub use app_check::AppCheck;
+
+mod batch;
+
+mod clock;
+
 mod firebase_auth;
-pub use firebase_auth::FirebaseAuth;
+pub use firebase_auth::{
+    get_app_check_configuration, get_configuration, get_discovery_configuration,
+    get_google_oidc_configuration, get_iap_configuration, get_x509_configuration,
+    parse_key_document, FirebaseAuth, FirebaseAuthBuilder, VerificationError,
+};
+
+mod google_auth;
+pub use google_auth::{GoogleOidcAuth, IapAuth};
+
+mod hash;
+
+mod inspect;
+
+mod jwt_verifier;
+pub use jwt_verifier::{AnyClaims, JwtVerifier};
+
+mod key_cache;
+
+mod key_fetcher;
+pub use key_fetcher::{KeyFetchResponse, KeyFetcher, ReqwestKeyFetcher};
+
+mod refresh;
+pub use refresh::{freshness_lifetime, BackoffPolicy, RefreshPolicy};
+
+mod replay;
+pub use replay::{InMemoryReplayStore, ReplayGuard, ReplayStore};
+
+mod spawner;
+pub use spawner::{Spawner, TokioSpawner};
 
 mod structs;
-pub use structs::{FirebaseUser, PublicKeysError, FirebaseProvider};
+pub use structs::{
+    AppCheckToken, FirebaseProvider, FirebaseUser, GoogleUser, IapUser, JwkConfiguration, JwkKey,
+    JwkKeys, JwtClaims, KeyStatus, PublicKeysError, RefreshEvent, SingleUse, TokenCheck,
+    TokenReport, VerificationCacheStats, VerifiedToken,
+};
+
+mod trace;
+
+mod token_verifier;
+pub use token_verifier::{TokenProfile, TokenVerifier};
+
+mod verification_cache;
+
+mod verify_options;
+pub use verify_options::VerifyOptions;
+
+mod x509;
+
+/// Mint signed Firebase ID tokens and serve their keys in tests, enabled by the `testing`
+/// feature.
+#[cfg(feature = "testing")]
+pub mod testing;
 
 #[cfg(feature = "actix-web")]
 mod actix_feature;
@@ -89,5 +151,11 @@ mod actix_feature;
 #[cfg(feature = "axum")]
 mod axum_feature;
 
+#[cfg(feature = "blocking")]
+mod blocking_feature;
+
+#[cfg(feature = "metrics")]
+mod metrics_feature;
+
 #[cfg(feature = "axum")]
 pub use axum_feature::FirebaseAuthState;
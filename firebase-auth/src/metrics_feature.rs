@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use metrics::{counter, gauge, histogram};
+
+use crate::VerificationError;
+
+/// Count the verification by outcome, `ok` or the [`VerificationError`] variant, and record
+/// its latency and the age of the keys it used. Every metric is labelled with the `jwk_url` of
+/// the configuration, so verifiers of different issuers are recorded separately.
+pub(crate) fn record_verification(
+    jwk_url: &str,
+    error: Option<&VerificationError>,
+    elapsed: Duration,
+    key_age: Duration,
+) {
+    let outcome = error.map_or("ok", VerificationError::kind);
+    let jwk_url = jwk_url.to_string();
+    counter!(
+        "firebase_auth_verifications_total",
+        "jwk_url" => jwk_url.clone(),
+        "outcome" => outcome
+    )
+    .increment(1);
+    histogram!("firebase_auth_verification_duration_seconds", "jwk_url" => jwk_url.clone())
+        .record(elapsed.as_secs_f64());
+    gauge!("firebase_auth_key_age_seconds", "jwk_url" => jwk_url).set(key_age.as_secs_f64());
+}
+
+/// Count a key refresh attempt and whether it failed.
+pub(crate) fn record_refresh(jwk_url: &str, success: bool) {
+    let jwk_url = jwk_url.to_string();
+    counter!("firebase_auth_key_refreshes_total", "jwk_url" => jwk_url.clone()).increment(1);
+    if success {
+        gauge!("firebase_auth_key_age_seconds", "jwk_url" => jwk_url).set(0.0);
+    } else {
+        counter!("firebase_auth_key_refresh_failures_total", "jwk_url" => jwk_url).increment(1);
+    }
+}
+
+/// Count a lookup in the verification cache.
+pub(crate) fn record_cache_lookup(jwk_url: &str, hit: bool) {
+    let jwk_url = jwk_url.to_string();
+    if hit {
+        counter!("firebase_auth_verification_cache_hits_total", "jwk_url" => jwk_url).increment(1);
+    } else {
+        counter!("firebase_auth_verification_cache_misses_total", "jwk_url" => jwk_url)
+            .increment(1);
+    }
+}
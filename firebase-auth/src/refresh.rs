@@ -0,0 +1,152 @@
+use std::time::{Duration, SystemTime};
+
+use rand::Rng;
+
+use crate::KeyFetchResponse;
+
+/// Freshness lifetime used when the key response has no caching headers at all.
+const FALLBACK_LIFETIME: Duration = Duration::from_secs(60);
+
+/// When to refresh the public keys, relative to how long the key response may be cached.
+#[derive(Debug, Clone)]
+pub struct RefreshPolicy {
+    /// Refresh after this fraction of the freshness lifetime has passed, e.g. `0.8`.
+    pub fraction: f64,
+    /// Randomly shorten the delay by up to this fraction of it, so that many instances
+    /// started together don't refresh at the same moment.
+    pub jitter: f64,
+    /// Lower bound of the delay, also used for `no-cache` responses.
+    pub min_delay: Duration,
+    /// Upper bound of the delay.
+    pub max_delay: Duration,
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self {
+            fraction: 0.8,
+            jitter: 0.1,
+            min_delay: Duration::from_secs(60),
+            max_delay: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl RefreshPolicy {
+    /// The delay until the next refresh of keys with the given freshness lifetime.
+    pub fn refresh_delay(&self, lifetime: Duration) -> Duration {
+        let jitter = random_fraction(self.jitter);
+        let delay = lifetime.as_secs_f64() * self.fraction.clamp(0.0, 1.0) * (1.0 - jitter);
+        Duration::try_from_secs_f64(delay)
+            .unwrap_or(Duration::MAX)
+            .clamp(self.min_delay, self.max_delay.max(self.min_delay))
+    }
+}
+
+/// How to retry when refreshing the public keys fails.
+#[derive(Debug, Clone)]
+pub struct BackoffPolicy {
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+    /// Factor applied to the delay after every further failure.
+    pub multiplier: f64,
+    /// Upper bound of the delay.
+    pub max_delay: Duration,
+    /// Randomly shorten the delay by up to this fraction of it.
+    pub jitter: f64,
+    /// How long the last good keys are still used after their freshness lifetime ran out.
+    /// Afterwards verification fails with
+    /// [`VerificationError::KeysExpired`](crate::VerificationError::KeysExpired).
+    pub max_staleness: Duration,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(5 * 60),
+            jitter: 0.5,
+            max_staleness: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl BackoffPolicy {
+    /// The delay before the next attempt after `failures` consecutive failed refreshes.
+    pub fn retry_delay(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+        let delay = Duration::try_from_secs_f64(delay)
+            .unwrap_or(Duration::MAX)
+            .min(self.max_delay);
+        delay.mul_f64(1.0 - random_fraction(self.jitter))
+    }
+}
+
+/// A random fraction between zero and `max`, which is clamped to `0.0..=1.0`.
+fn random_fraction(max: f64) -> f64 {
+    let max = max.clamp(0.0, 1.0);
+    if max > 0.0 {
+        rand::thread_rng().gen_range(0.0..=max)
+    } else {
+        0.0
+    }
+}
+
+fn cache_directives(cache_control: &str) -> Vec<(String, Option<&str>)> {
+    cache_control
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty())
+        .map(|directive| match directive.split_once('=') {
+            Some((key, val)) => (
+                key.trim().to_lowercase(),
+                Some(val.trim().trim_matches('"')),
+            ),
+            None => (directive.to_lowercase(), None),
+        })
+        .collect()
+}
+
+fn seconds(value: Option<&str>) -> Option<Duration> {
+    value?.parse().ok().map(Duration::from_secs)
+}
+
+fn http_date(response: &KeyFetchResponse, name: &str) -> Option<SystemTime> {
+    httpdate::parse_http_date(response.header(name)?).ok()
+}
+
+/// How long the response may be used, following the HTTP caching rules: `no-cache` and
+/// `no-store` are never fresh, `max-age` (or `s-maxage`) takes precedence over `Expires`, and
+/// the age of the response is subtracted. Without any of these headers a short fallback
+/// lifetime is used.
+pub fn freshness_lifetime(response: &KeyFetchResponse, now: SystemTime) -> Duration {
+    let directives = cache_directives(response.header("Cache-Control").unwrap_or(""));
+    let directive = |name: &str| directives.iter().find(|(key, _)| key == name);
+
+    if directive("no-cache").is_some() || directive("no-store").is_some() {
+        return Duration::ZERO;
+    }
+
+    let date = http_date(response, "Date");
+    let lifetime = match directive("max-age").or_else(|| directive("s-maxage")) {
+        Some((_, value)) => seconds(*value),
+        // An invalid Expires value means the response is already stale.
+        None => response.header("Expires").map(|_| {
+            http_date(response, "Expires")
+                .and_then(|expires| expires.duration_since(date.unwrap_or(now)).ok())
+                .unwrap_or(Duration::ZERO)
+        }),
+    };
+    let Some(lifetime) = lifetime else {
+        return FALLBACK_LIFETIME;
+    };
+
+    let apparent_age = date
+        .and_then(|date| now.duration_since(date).ok())
+        .unwrap_or(Duration::ZERO);
+    let age = seconds(response.header("Age")).unwrap_or(Duration::ZERO);
+
+    lifetime.saturating_sub(apparent_age.max(age))
+}
@@ -0,0 +1,86 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use futures::future::BoxFuture;
+use lru::LruCache;
+
+use crate::clock::unix_now;
+use crate::hash::sha256_hex;
+use crate::VerificationError;
+
+/// Remembers which tokens were already used. Implement it to share the used tokens between
+/// instances, e.g. with Redis `SET key 1 NX EXAT expires_at`.
+pub trait ReplayStore: Send + Sync {
+    /// Remember `key` until the unix time `expires_at`. Returns `false` when `key` is already
+    /// remembered, i.e. the token is replayed. Return `false` as well when the store is
+    /// unavailable, so that sensitive endpoints fail closed.
+    fn insert_once<'a>(&'a self, key: &'a str, expires_at: u64) -> BoxFuture<'a, bool>;
+}
+
+/// A [`ReplayStore`] for a single instance, keeping the most recently used tokens. When more
+/// than `capacity` unexpired tokens are used, the least recently used ones are forgotten.
+pub struct InMemoryReplayStore {
+    entries: Mutex<LruCache<String, u64>>,
+}
+
+impl InMemoryReplayStore {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+}
+
+impl ReplayStore for InMemoryReplayStore {
+    fn insert_once<'a>(&'a self, key: &'a str, expires_at: u64) -> BoxFuture<'a, bool> {
+        let now = unix_now();
+
+        let mut entries = self.entries.lock().unwrap();
+        let replayed = entries.get(key).is_some_and(|&exp| exp > now);
+        if !replayed {
+            entries.put(key.to_owned(), expires_at);
+        }
+        Box::pin(futures::future::ready(!replayed))
+    }
+}
+
+/// Accepts every token only once within its lifetime, see
+/// [`FirebaseAuth::verify_once`](crate::FirebaseAuth::verify_once). Tokens are stored by
+/// their SHA-256 hash.
+#[derive(Clone)]
+pub struct ReplayGuard {
+    store: Arc<dyn ReplayStore>,
+}
+
+impl Default for ReplayGuard {
+    /// An [`InMemoryReplayStore`] for 10,000 tokens.
+    fn default() -> Self {
+        ReplayGuard::in_memory(10_000)
+    }
+}
+
+impl ReplayGuard {
+    pub fn new(store: impl ReplayStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+
+    pub fn in_memory(capacity: usize) -> Self {
+        ReplayGuard::new(InMemoryReplayStore::new(capacity))
+    }
+
+    /// Fail with [`VerificationError::TokenReplayed`] when the token was already checked.
+    pub async fn check(&self, token: &str, expires_at: u64) -> Result<(), VerificationError> {
+        let key = sha256_hex(token, 32);
+
+        if self.store.insert_once(&key, expires_at).await {
+            Ok(())
+        } else {
+            Err(VerificationError::TokenReplayed)
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+
+/// Run the background key refresh on an async runtime. Implement it to refresh the keys on
+/// another executor than tokio, or on a dedicated runtime.
+pub trait Spawner: Send + Sync {
+    /// Run `task` in the background until it completes.
+    fn spawn(&self, task: BoxFuture<'static, ()>);
+
+    /// Wait for `duration`, using the timer of the runtime.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// The default [`Spawner`], running the refresh on the current tokio runtime.
+#[derive(Debug, Clone, Default)]
+pub struct TokioSpawner;
+
+impl Spawner for TokioSpawner {
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        tokio::spawn(task);
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
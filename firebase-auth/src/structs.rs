@@ -1,14 +1,32 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
+use jsonwebtoken::Algorithm;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 
-#[allow(dead_code)]
-#[derive(Debug)]
+use crate::VerificationError;
+
+/// Describes where the signing keys of a token issuer are published and which claims the
+/// tokens must carry. See [`FirebaseAuth::with_configuration`](crate::FirebaseAuth::with_configuration).
+#[derive(Debug, Clone)]
 pub struct JwkConfiguration {
     pub jwk_url: String,
     pub audience: String,
+    pub issuers: Vec<String>,
+    /// Token algorithms accepted by the verifier. The signing key must match the algorithm.
+    pub algorithms: Vec<Algorithm>,
+    /// Accept unsigned tokens when `FIREBASE_AUTH_EMULATOR_HOST` is set.
+    pub allow_emulator: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenIdConfiguration {
     pub issuer: String,
+    pub jwks_uri: String,
+    pub id_token_signing_alg_values_supported: Option<Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -16,14 +34,43 @@ pub struct KeyResponse {
     pub keys: Vec<JwkKey>,
 }
 
+/// The public keys are published either as a JWKS document or as a `kid -> PEM certificate` map.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum KeyDocument {
+    Jwks(KeyResponse),
+    Certificates(HashMap<String, String>),
+}
+
 #[allow(dead_code)]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct JwkKey {
-    pub e: String,
-    pub alg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
     pub kty: String,
     pub kid: String,
-    pub n: String,
+    /// RSA modulus
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    /// RSA exponent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    /// EC or OKP curve
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    /// EC x coordinate or OKP public key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    /// EC y coordinate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+    /// Start of the certificate validity period for keys read from X.509 certificates, as
+    /// unix timestamp in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<i64>,
+    /// End of the certificate validity period for keys read from X.509 certificates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<i64>,
 }
 
 /// The Jwt claims decoded from the user token. Can also be viewed as the Firebase User
@@ -45,6 +92,78 @@ pub struct FirebaseUser {
     pub firebase: FirebaseProvider,
 }
 
+/// The claims of a verified token together with details of its signature, see
+/// [`FirebaseAuth::verify_token`](crate::FirebaseAuth::verify_token). Also an extractor for
+/// Bearer tokens.
+#[derive(Debug, Clone)]
+pub struct VerifiedToken<T = FirebaseUser> {
+    pub claims: T,
+    /// The `kid` of the key which signed the token.
+    pub kid: String,
+    pub algorithm: Algorithm,
+    /// The raw token.
+    pub token: String,
+    /// The `exp` claim as unix time.
+    pub expires_at: u64,
+}
+
+impl<T> VerifiedToken<T> {
+    /// The time until the token expires, e.g. to set `Cache-Control: max-age` of a response.
+    pub fn remaining_lifetime(&self) -> Duration {
+        let expires_at = UNIX_EPOCH + Duration::from_secs(self.expires_at);
+        expires_at
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+/// Extract the claims of a Bearer token which is accepted only once, for sensitive endpoints
+/// like password changes. See [`FirebaseAuth::verify_once`](crate::FirebaseAuth::verify_once).
+pub struct SingleUse<T = FirebaseUser>(pub T);
+
+/// Extract the claims of a Bearer token verified by a
+/// [`JwtVerifier`](crate::JwtVerifier), e.g. for Auth0 tokens next to Firebase ID tokens.
+pub struct JwtClaims<T>(pub T);
+
+/// The Jwt claims of a request signed by Google Cloud Identity-Aware Proxy. Read from the
+/// `x-goog-iap-jwt-assertion` header.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IapUser {
+    pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+    pub email: String,
+    pub hd: Option<String>,
+    pub google: Option<Map<String, Value>>,
+}
+
+/// The Jwt claims of a Google-signed OIDC token, e.g. Cloud Run service-to-service calls.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GoogleUser {
+    pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+    pub azp: Option<String>,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+}
+
+/// The Jwt claims of a Firebase App Check token. Read from the `X-Firebase-AppCheck` header.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AppCheckToken {
+    pub iss: String,
+    /// Contains both `projects/PROJECT_NUMBER` and `projects/PROJECT_ID`.
+    pub aud: Vec<String>,
+    /// The Firebase App ID of the app which obtained the token.
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct FirebaseProvider {
     sign_in_provider: String,
@@ -57,12 +176,89 @@ pub struct JwkKeys {
     pub max_age: Duration,
 }
 
+/// Content of the on-disk key cache, see
+/// [`FirebaseAuthBuilder::key_cache_file`](crate::FirebaseAuthBuilder::key_cache_file).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyCacheFile {
+    pub jwk_url: String,
+    /// Unix timestamp in seconds after which the keys must be fetched again.
+    pub expires_at: u64,
+    pub keys: Vec<JwkKey>,
+}
+
+/// The state of the background key refresh, see [`FirebaseAuth::status`](crate::FirebaseAuth::status).
+#[derive(Debug, Clone)]
+pub struct KeyStatus {
+    /// When the current keys were fetched or loaded.
+    pub last_refresh: Option<SystemTime>,
+    /// When the next refresh is scheduled. `None` for static keys, or when the delay is too
+    /// long to represent.
+    pub next_refresh: Option<SystemTime>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    /// The `kid`s of the current keys.
+    pub kids: Vec<String>,
+    /// `false` for static keys or when the refresh task stopped, was shut down or panicked.
+    pub refresh_task_running: bool,
+}
+
+/// Counters of the verification cache, see
+/// [`FirebaseAuth::cache_stats`](crate::FirebaseAuth::cache_stats).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerificationCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of remembered tokens, including expired ones not yet evicted.
+    pub entries: usize,
+}
+
+/// The outcome of a key refresh, see [`FirebaseAuth::subscribe`](crate::FirebaseAuth::subscribe).
+#[derive(Debug, Clone)]
+pub enum RefreshEvent {
+    /// The initial keys were loaded when the instance was created.
+    Loaded { kids: Vec<String> },
+    Refreshed {
+        kids: Vec<String>,
+        next_refresh: Duration,
+    },
+    Failed {
+        error: String,
+        consecutive_failures: u32,
+        retry_in: Duration,
+    },
+}
+
+/// One step of the verification, see [`FirebaseAuth::inspect`](crate::FirebaseAuth::inspect).
+#[derive(Debug, Clone)]
+pub struct TokenCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// The decoded parts of a token and the outcome of each verification step, see
+/// [`FirebaseAuth::inspect`](crate::FirebaseAuth::inspect). `header` and `claims` are decoded
+/// without verification.
+#[derive(Debug)]
+pub struct TokenReport {
+    pub header: Option<Value>,
+    pub claims: Option<Value>,
+    pub kid: Option<String>,
+    pub checks: Vec<TokenCheck>,
+    pub result: Result<(), VerificationError>,
+}
+
 #[derive(Debug)]
 pub enum PublicKeysError {
     CouldntFetchPublicKeys(reqwest::Error),
-    NoCacheControlHeader,
-    MaxAgeValueEmpty,
-    NonNumericMaxAge,
-    NoMaxAgeSpecified,
-    CannotParsePublicKey(reqwest::Error),
+    CannotParsePublicKey(serde_json::Error),
+    CannotParseDiscovery(serde_json::Error),
+    DiscoveryIssuerMismatch,
+    UnexpectedStatus(u16),
+    CannotParseCertificate,
+    /// Error reported by a custom [`KeyFetcher`](crate::KeyFetcher).
+    FetchFailed(Box<dyn std::error::Error + Send + Sync>),
+    /// A custom [`KeyFetcher`](crate::KeyFetcher) is async and cannot fetch the keys of
+    /// `build_blocking`, which takes a `blocking_client` instead.
+    UnsupportedKeyFetcher,
 }
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use rand::Rng;
+use rsa::{pkcs1::EncodeRsaPrivateKey, traits::PublicKeyParts, RsaPrivateKey};
+use serde_json::{json, Value};
+
+use crate::clock::unix_now;
+use crate::{get_configuration, FirebaseAuth, JwkKey, JwkKeys};
+
+mod mock_server;
+pub use mock_server::MockJwksServer;
+
+/// A Firebase project with its own RSA key pair, minting signed ID tokens for tests without
+/// network access. Generating the key is slow in unoptimized builds, so share one issuer
+/// between tests where possible.
+///
+/// ```rust
+/// use firebase_auth::{testing::TestIssuer, FirebaseUser};
+///
+/// let issuer = TestIssuer::new("my-project-id");
+/// let firebase_auth = issuer.firebase_auth();
+///
+/// let token = issuer.token("user-1").claim("email", "user@example.com").sign();
+/// let user: FirebaseUser = firebase_auth.verify(&token).unwrap();
+/// assert_eq!(user.email.as_deref(), Some("user@example.com"));
+/// ```
+pub struct TestIssuer {
+    project_id: String,
+    kid: String,
+    encoding_key: EncodingKey,
+    jwk: JwkKey,
+}
+
+impl TestIssuer {
+    /// Generate a 2048 bit RSA key pair with a random `kid`.
+    pub fn new(project_id: &str) -> TestIssuer {
+        let kid = format!("test-{:016x}", rand::thread_rng().gen::<u64>());
+        TestIssuer::with_kid(project_id, &kid)
+    }
+
+    /// Generate a 2048 bit RSA key pair published under `kid`.
+    pub fn with_kid(project_id: &str, kid: &str) -> TestIssuer {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048)
+            .expect("failed to generate an RSA key pair");
+        let der = private_key
+            .to_pkcs1_der()
+            .expect("failed to encode the RSA private key");
+
+        TestIssuer {
+            project_id: project_id.to_owned(),
+            kid: kid.to_owned(),
+            encoding_key: EncodingKey::from_rsa_der(der.as_bytes()),
+            jwk: JwkKey {
+                alg: Some("RS256".to_owned()),
+                kty: "RSA".to_owned(),
+                kid: kid.to_owned(),
+                n: Some(BASE64_URL_SAFE_NO_PAD.encode(private_key.n().to_bytes_be())),
+                e: Some(BASE64_URL_SAFE_NO_PAD.encode(private_key.e().to_bytes_be())),
+                crv: None,
+                x: None,
+                y: None,
+                not_before: None,
+                not_after: None,
+            },
+        }
+    }
+
+    pub fn project_id(&self) -> &str {
+        &self.project_id
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    /// The public key of the issuer.
+    pub fn jwk(&self) -> JwkKey {
+        self.jwk.clone()
+    }
+
+    /// The public key of the issuer as a key set, fresh for an hour.
+    pub fn jwk_keys(&self) -> JwkKeys {
+        JwkKeys {
+            keys: vec![self.jwk()],
+            max_age: Duration::from_secs(3600),
+        }
+    }
+
+    /// The public key of the issuer as a JWKS document.
+    pub fn jwks_document(&self) -> String {
+        json!({ "keys": [self.jwk] }).to_string()
+    }
+
+    /// A [`FirebaseAuth`] for the project which trusts only this issuer.
+    pub fn firebase_auth(&self) -> FirebaseAuth {
+        FirebaseAuth::with_static_keys(get_configuration(&self.project_id), vec![self.jwk()])
+    }
+
+    /// Start a valid Firebase ID token for `uid`, signed in with a password and expiring in an
+    /// hour.
+    pub fn token(&self, uid: &str) -> TokenBuilder<'_> {
+        let now = unix_now();
+        TokenBuilder {
+            issuer: self,
+            kid: self.kid.clone(),
+            claims: json!({
+                "iss": format!("https://securetoken.google.com/{}", self.project_id),
+                "aud": self.project_id,
+                "sub": uid,
+                "user_id": uid,
+                "iat": now,
+                "exp": now + 3600,
+                "auth_time": now,
+                "firebase": {
+                    "sign_in_provider": "password",
+                    "identities": {},
+                },
+            }),
+        }
+    }
+}
+
+/// Adjust the claims and header of a token before signing it, see [`TestIssuer::token`].
+pub struct TokenBuilder<'a> {
+    issuer: &'a TestIssuer,
+    kid: String,
+    claims: Value,
+}
+
+impl TokenBuilder<'_> {
+    /// Set or replace a claim.
+    pub fn claim(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.claims[name] = value.into();
+        self
+    }
+
+    /// Remove a claim, e.g. to test tokens missing a required claim.
+    pub fn without_claim(mut self, name: &str) -> Self {
+        if let Some(claims) = self.claims.as_object_mut() {
+            claims.remove(name);
+        }
+        self
+    }
+
+    pub fn audience(self, audience: &str) -> Self {
+        self.claim("aud", audience)
+    }
+
+    pub fn issuer(self, issuer: &str) -> Self {
+        self.claim("iss", issuer)
+    }
+
+    pub fn sign_in_provider(mut self, provider: &str) -> Self {
+        self.claims["firebase"]["sign_in_provider"] = provider.into();
+        self
+    }
+
+    /// Expire the token `duration` from now.
+    pub fn expires_in(self, duration: Duration) -> Self {
+        self.expires_at(unix_now().saturating_add(duration.as_secs()))
+    }
+
+    /// Expire the token at the given unix time, e.g. in the past.
+    pub fn expires_at(self, exp: u64) -> Self {
+        self.claim("exp", exp)
+    }
+
+    /// Set the `iat` and `auth_time` claims to the given unix time.
+    pub fn issued_at(self, iat: u64) -> Self {
+        self.claim("iat", iat).claim("auth_time", iat)
+    }
+
+    /// Put another `kid` in the header, e.g. one unknown to the verifier.
+    pub fn kid(mut self, kid: &str) -> Self {
+        self.kid = kid.to_owned();
+        self
+    }
+
+    /// Sign the token with the key of the issuer.
+    pub fn sign(self) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = Some(self.kid);
+        encode(&header, &self.claims, &self.issuer.encoding_key).expect("failed to sign the token")
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use serde_json::json;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+    time::sleep,
+};
+
+use crate::{get_configuration, JwkConfiguration, JwkKey};
+
+struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    keys: Vec<JwkKey>,
+    delay: Duration,
+}
+
+/// A local HTTP server publishing a JWKS document, to test the key refresh end to end. The
+/// keys, headers, status and latency of the responses can be changed while it runs.
+///
+/// ```rust,no_run
+/// use firebase_auth::{testing::{MockJwksServer, TestIssuer}, FirebaseAuth};
+///
+/// # async fn run() {
+/// let issuer = TestIssuer::new("my-project-id");
+/// let server = MockJwksServer::start(vec![issuer.jwk()]).await;
+///
+/// let firebase_auth = FirebaseAuth::builder(server.configuration("my-project-id"))
+///     .build()
+///     .await
+///     .unwrap();
+///
+/// // Rotate to a new key, served from the next refresh on.
+/// let next_issuer = TestIssuer::new("my-project-id");
+/// server.set_keys(vec![next_issuer.jwk()]);
+/// # }
+/// ```
+pub struct MockJwksServer {
+    url: String,
+    response: Arc<Mutex<MockResponse>>,
+    requests: Arc<AtomicUsize>,
+    handler: JoinHandle<()>,
+}
+
+impl MockJwksServer {
+    /// Listen on a random local port and serve `keys` with `Cache-Control: max-age=3600`.
+    pub async fn start(keys: Vec<JwkKey>) -> MockJwksServer {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind the mock JWKS server");
+        let url = format!("http://{}/jwks", listener.local_addr().unwrap());
+
+        let response = Arc::new(Mutex::new(MockResponse {
+            status: 200,
+            headers: vec![("Cache-Control".to_owned(), "max-age=3600".to_owned())],
+            keys,
+            delay: Duration::ZERO,
+        }));
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let handler = tokio::spawn({
+            let response = Arc::clone(&response);
+            let requests = Arc::clone(&requests);
+            async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    tokio::spawn(respond(socket, Arc::clone(&response)));
+                }
+            }
+        });
+
+        MockJwksServer {
+            url,
+            response,
+            requests,
+            handler,
+        }
+    }
+
+    /// The url of the JWKS document.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The Firebase configuration of `project_id`, with the keys served by this server.
+    pub fn configuration(&self, project_id: &str) -> JwkConfiguration {
+        JwkConfiguration {
+            jwk_url: self.url.clone(),
+            ..get_configuration(project_id)
+        }
+    }
+
+    /// The number of requests received so far.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Serve other keys from now on, e.g. to rotate keys.
+    pub fn set_keys(&self, keys: Vec<JwkKey>) {
+        self.response.lock().unwrap().keys = keys;
+    }
+
+    /// Respond with `status` from now on, without a body unless it is a success status.
+    pub fn set_status(&self, status: u16) {
+        self.response.lock().unwrap().status = status;
+    }
+
+    /// Set a response header, replacing any header with the same name.
+    pub fn set_header(&self, name: &str, value: &str) {
+        let mut response = self.response.lock().unwrap();
+        response
+            .headers
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+        response.headers.push((name.to_owned(), value.to_owned()));
+    }
+
+    /// Remove a response header, e.g. `Cache-Control`.
+    pub fn remove_header(&self, name: &str) {
+        let mut response = self.response.lock().unwrap();
+        response
+            .headers
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    }
+
+    /// Wait `delay` before every response, e.g. to test timeouts.
+    pub fn set_delay(&self, delay: Duration) {
+        self.response.lock().unwrap().delay = delay;
+    }
+}
+
+impl Drop for MockJwksServer {
+    fn drop(&mut self) {
+        self.handler.abort();
+    }
+}
+
+async fn respond(mut socket: TcpStream, response: Arc<Mutex<MockResponse>>) {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        match socket.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    let (status, headers, body, delay) = {
+        let response = response.lock().unwrap();
+        let body = if (200..300).contains(&response.status) {
+            json!({ "keys": response.keys }).to_string()
+        } else {
+            String::new()
+        };
+        let headers: String = response
+            .headers
+            .iter()
+            .map(|(key, value)| format!("{}: {}\r\n", key, value))
+            .collect();
+        (response.status, headers, body, response.delay)
+    };
+    sleep(delay).await;
+
+    let message = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    );
+    let _ = socket.write_all(message.as_bytes()).await;
+    let _ = socket.shutdown().await;
+}
@@ -0,0 +1,81 @@
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    FirebaseAuth, JwkConfiguration, KeyStatus, PublicKeysError, VerificationError, VerifyOptions,
+};
+
+/// The claims of the tokens of one Google issuer, together with the configuration that
+/// verifies them, see [`TokenVerifier`].
+pub trait TokenProfile: DeserializeOwned {
+    /// The configuration for tokens issued to `audience`, the value each profile documents
+    /// for [`TokenVerifier::new`].
+    fn configuration(audience: &str) -> JwkConfiguration;
+}
+
+/// Verifies the tokens of one [`TokenProfile`], e.g. [`IapAuth`](crate::IapAuth) or
+/// [`AppCheck`](crate::AppCheck). The keys are fetched and refreshed like those of
+/// [`FirebaseAuth`]. A separate type from [`FirebaseAuth`], so one app can keep a verifier
+/// per kind of token in its state.
+///
+/// Clones share the keys and the refresh task.
+pub struct TokenVerifier<C> {
+    pub(crate) firebase_auth: FirebaseAuth,
+    claims: PhantomData<fn() -> C>,
+}
+
+impl<C> Clone for TokenVerifier<C> {
+    fn clone(&self) -> Self {
+        TokenVerifier::from_firebase_auth(self.firebase_auth.clone())
+    }
+}
+
+impl<C> TokenVerifier<C> {
+    /// Verify the tokens with a [`FirebaseAuth`] built from the configuration of the profile,
+    /// e.g. with a custom key fetcher or static keys.
+    pub fn from_firebase_auth(firebase_auth: FirebaseAuth) -> Self {
+        TokenVerifier {
+            firebase_auth,
+            claims: PhantomData,
+        }
+    }
+
+    /// The state of the key refresh, see [`FirebaseAuth::status`].
+    pub fn status(&self) -> KeyStatus {
+        self.firebase_auth.status()
+    }
+
+    /// Stop the key refresh, see [`FirebaseAuth::shutdown`].
+    pub async fn shutdown(&self) {
+        self.firebase_auth.shutdown().await
+    }
+}
+
+impl<C: TokenProfile> TokenVerifier<C> {
+    /// Panics when the keys cannot be fetched, like [`FirebaseAuth::new`].
+    pub async fn new(audience: &str) -> Self {
+        TokenVerifier::from_firebase_auth(
+            FirebaseAuth::with_configuration(C::configuration(audience)).await,
+        )
+    }
+
+    pub async fn try_new(audience: &str) -> Result<Self, PublicKeysError> {
+        let firebase_auth =
+            FirebaseAuth::try_with_configuration(C::configuration(audience)).await?;
+        Ok(TokenVerifier::from_firebase_auth(firebase_auth))
+    }
+
+    pub fn verify(&self, token: &str) -> Result<C, VerificationError> {
+        self.firebase_auth.verify(token)
+    }
+
+    /// Verify the token with a per-request policy, see [`FirebaseAuth::verify_with`].
+    pub fn verify_with(
+        &self,
+        token: &str,
+        options: &VerifyOptions,
+    ) -> Result<C, VerificationError> {
+        self.firebase_auth.verify_with(token, options)
+    }
+}
@@ -0,0 +1,50 @@
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::decode_header;
+use serde::Deserialize;
+use tracing::Span;
+
+use crate::hash::sha256_hex;
+use crate::VerificationError;
+
+#[derive(Deserialize)]
+struct TraceClaims {
+    sub: Option<String>,
+    firebase: Option<TraceFirebase>,
+}
+
+#[derive(Deserialize)]
+struct TraceFirebase {
+    sign_in_provider: Option<String>,
+}
+
+/// Identify a token in logs without revealing it: the first 8 bytes of its SHA-256 hash.
+pub(crate) fn token_fingerprint(token: &str) -> String {
+    sha256_hex(token, 8)
+}
+
+/// Record `kid`, `outcome` and, for verified tokens, `uid` and `sign_in_provider` on the
+/// `verify_token` span.
+pub(crate) fn record_verification(span: &Span, token: &str, error: Option<&VerificationError>) {
+    if let Some(kid) = decode_header(token).ok().and_then(|header| header.kid) {
+        span.record("kid", kid);
+    }
+    span.record("outcome", error.map_or("ok", VerificationError::kind));
+
+    // Only claims of a verified token are trusted enough to end up in the logs.
+    if error.is_some() {
+        return;
+    }
+    let claims = token
+        .split('.')
+        .nth(1)
+        .and_then(|payload| BASE64_URL_SAFE_NO_PAD.decode(payload).ok())
+        .and_then(|payload| serde_json::from_slice::<TraceClaims>(&payload).ok());
+    if let Some(claims) = claims {
+        if let Some(uid) = claims.sub {
+            span.record("uid", uid);
+        }
+        if let Some(provider) = claims.firebase.and_then(|f| f.sign_in_provider) {
+            span.record("sign_in_provider", provider);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use lru::LruCache;
+use serde_json::Value;
+
+use crate::clock::unix_now;
+use crate::hash::sha256;
+use crate::structs::VerificationCacheStats;
+
+struct CachedClaims {
+    claims: Value,
+    expires_at: u64,
+    key_generation: u64,
+}
+
+/// Remembers the claims of verified tokens by their SHA-256 hash until they expire or the
+/// keys rotate, see [`FirebaseAuth::with_verification_cache`].
+///
+/// [`FirebaseAuth::with_verification_cache`]: crate::FirebaseAuth::with_verification_cache
+pub(crate) struct VerificationCache {
+    entries: Mutex<LruCache<[u8; 32], CachedClaims>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl VerificationCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The claims of `token` if it was verified with the current keys and is not expired.
+    pub(crate) fn get(&self, token: &str, key_generation: u64) -> Option<Value> {
+        let key = sha256(token);
+        let mut entries = self.entries.lock().unwrap();
+
+        let claims = match entries.get(&key) {
+            Some(entry)
+                if entry.key_generation == key_generation && entry.expires_at > unix_now() =>
+            {
+                Some(entry.claims.clone())
+            }
+            Some(_) => {
+                entries.pop(&key);
+                None
+            }
+            None => None,
+        };
+        drop(entries);
+
+        if claims.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        claims
+    }
+
+    /// Remember the claims of a verified token until its `exp`. Tokens without `exp` or
+    /// accepted only within the leeway are not remembered.
+    pub(crate) fn insert(&self, token: &str, claims: &Value, key_generation: u64) {
+        let Some(expires_at) = claims["exp"].as_u64() else {
+            return;
+        };
+        if expires_at <= unix_now() {
+            return;
+        }
+
+        self.entries.lock().unwrap().put(
+            sha256(token),
+            CachedClaims {
+                claims: claims.clone(),
+                expires_at,
+                key_generation,
+            },
+        );
+    }
+
+    pub(crate) fn stats(&self) -> VerificationCacheStats {
+        VerificationCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::clock::unix_now;
+use crate::VerificationError;
+
+/// A verification policy for a single request, see [`FirebaseAuth::verify_with`]. The default
+/// applies only the checks of the [`JwkConfiguration`](crate::JwkConfiguration).
+/// The extractors pick it up from the request: add it with `.app_data(options)` to an Actix
+/// resource or scope, or with `.layer(Extension(options))` to an Axum route.
+///
+/// [`FirebaseAuth::verify_with`]: crate::FirebaseAuth::verify_with
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// Clock skew tolerated for `exp`. `None` keeps the default of 60 seconds.
+    pub leeway: Option<Duration>,
+    /// Accept these audiences besides the one of the configuration.
+    pub audiences: Vec<String>,
+    /// Reject tokens missing any of these claims with [`VerificationError::MissingClaim`].
+    pub required_claims: Vec<String>,
+    /// Reject tokens issued longer ago than this, according to the `iat` claim, with
+    /// [`VerificationError::TokenTooOld`].
+    pub max_token_age: Option<Duration>,
+    /// Reject tokens of users who signed in longer ago than this, according to the
+    /// `auth_time` claim, with [`VerificationError::StaleAuthentication`]. Refreshed tokens
+    /// keep the original `auth_time`, so this requires a recent login.
+    pub max_auth_age: Option<Duration>,
+    /// Accept only users signed in with one of these providers, e.g. `password` or
+    /// `google.com`, according to the `firebase.sign_in_provider` claim. Empty accepts all.
+    pub sign_in_providers: Vec<String>,
+    /// Reject users without a verified email with [`VerificationError::EmailNotVerified`].
+    pub require_email_verified: bool,
+}
+
+/// Whether the claim `name` is older than `max_age`. Missing claims count as too old.
+fn older_than(claims: &Value, name: &str, max_age: Duration, now: u64) -> bool {
+    claims[name]
+        .as_u64()
+        .is_none_or(|time| now.saturating_sub(time) > max_age.as_secs())
+}
+
+impl VerifyOptions {
+    /// Whether any check needs the claims besides the standard validation.
+    pub(crate) fn checks_claims(&self) -> bool {
+        !self.required_claims.is_empty()
+            || self.max_token_age.is_some()
+            || self.max_auth_age.is_some()
+            || !self.sign_in_providers.is_empty()
+            || self.require_email_verified
+    }
+
+    pub(crate) fn check_claims(&self, claims: &Value) -> Result<(), VerificationError> {
+        let now = unix_now();
+
+        if self
+            .required_claims
+            .iter()
+            .any(|name| claims.get(name).is_none_or(Value::is_null))
+        {
+            return Err(VerificationError::MissingClaim);
+        }
+
+        if let Some(max_token_age) = self.max_token_age {
+            if older_than(claims, "iat", max_token_age, now) {
+                return Err(VerificationError::TokenTooOld);
+            }
+        }
+
+        if let Some(max_auth_age) = self.max_auth_age {
+            if older_than(claims, "auth_time", max_auth_age, now) {
+                return Err(VerificationError::StaleAuthentication);
+            }
+        }
+
+        if !self.sign_in_providers.is_empty() {
+            let provider = claims["firebase"]["sign_in_provider"].as_str();
+            if !provider
+                .is_some_and(|provider| self.sign_in_providers.iter().any(|p| p == provider))
+            {
+                return Err(VerificationError::SignInProviderNotAllowed);
+            }
+        }
+
+        if self.require_email_verified && claims["email_verified"] != Value::Bool(true) {
+            return Err(VerificationError::EmailNotVerified);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use simple_asn1::{from_der, ASN1Block};
+
+use crate::structs::{JwkKey, PublicKeysError};
+
+/// Extract the RSA public key of a PEM encoded X.509 certificate, as published by
+/// `https://www.googleapis.com/robot/v1/metadata/x509/securetoken@system.gserviceaccount.com`.
+pub fn rsa_key_from_certificate(kid: &str, certificate: &str) -> Result<JwkKey, PublicKeysError> {
+    let pem = pem::parse(certificate).map_err(|_| PublicKeysError::CannotParseCertificate)?;
+    if pem.tag() != "CERTIFICATE" {
+        return Err(PublicKeysError::CannotParseCertificate);
+    }
+
+    let certificate =
+        parse_certificate(pem.contents()).ok_or(PublicKeysError::CannotParseCertificate)?;
+
+    Ok(JwkKey {
+        alg: Some("RS256".to_owned()),
+        kty: "RSA".to_owned(),
+        kid: kid.to_owned(),
+        n: Some(BASE64_URL_SAFE_NO_PAD.encode(certificate.n)),
+        e: Some(BASE64_URL_SAFE_NO_PAD.encode(certificate.e)),
+        crv: None,
+        x: None,
+        y: None,
+        not_before: Some(certificate.not_before),
+        not_after: Some(certificate.not_after),
+    })
+}
+
+struct Certificate {
+    n: Vec<u8>,
+    e: Vec<u8>,
+    not_before: i64,
+    not_after: i64,
+}
+
+fn sequence(block: &ASN1Block) -> Option<&[ASN1Block]> {
+    match block {
+        ASN1Block::Sequence(_, items) => Some(items),
+        _ => None,
+    }
+}
+
+fn unix_time(block: &ASN1Block) -> Option<i64> {
+    match block {
+        ASN1Block::UTCTime(_, time) | ASN1Block::GeneralizedTime(_, time) => {
+            Some(time.assume_utc().unix_timestamp())
+        }
+        _ => None,
+    }
+}
+
+fn unsigned_integer(block: &ASN1Block) -> Option<Vec<u8>> {
+    match block {
+        ASN1Block::Integer(_, value) => Some(value.to_bytes_be().1),
+        _ => None,
+    }
+}
+
+fn parse_certificate(der: &[u8]) -> Option<Certificate> {
+    // Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm, signatureValue }
+    let blocks = from_der(der).ok()?;
+    let tbs_certificate = sequence(sequence(blocks.first()?)?.first()?)?;
+
+    // The version is an optional explicitly tagged field in front of the serial number.
+    let fields = match tbs_certificate.first()? {
+        ASN1Block::Explicit(..) => &tbs_certificate[1..],
+        _ => tbs_certificate,
+    };
+
+    // serialNumber, signature, issuer, validity, subject, subjectPublicKeyInfo
+    let validity = sequence(fields.get(3)?)?;
+    let public_key_info = sequence(fields.get(5)?)?;
+    let public_key = match public_key_info.get(1)? {
+        ASN1Block::BitString(_, _, bytes) => bytes,
+        _ => return None,
+    };
+
+    // RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }
+    let rsa_blocks = from_der(public_key).ok()?;
+    let rsa_key = sequence(rsa_blocks.first()?)?;
+    Some(Certificate {
+        n: unsigned_integer(rsa_key.first()?)?,
+        e: unsigned_integer(rsa_key.get(1)?)?,
+        not_before: unix_time(validity.first()?)?,
+        not_after: unix_time(validity.get(1)?)?,
+    })
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use firebase_auth::{parse_key_document, FirebaseAuth, VerificationError};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::{json, Value};
+
+fn firebase_auth(keys: Vec<Value>, algorithms: Vec<Algorithm>) -> FirebaseAuth {
+    let keys = parse_key_document(&json!({ "keys": keys }).to_string()).unwrap();
+    let mut config = common::config("http://localhost/jwks");
+    config.algorithms = algorithms;
+    FirebaseAuth::with_static_keys(config, keys)
+}
+
+fn all_algorithms() -> Vec<Algorithm> {
+    vec![Algorithm::RS256, Algorithm::ES256, Algorithm::EdDSA]
+}
+
+fn rsa_jwk() -> Value {
+    common::jwks()["keys"][0].clone()
+}
+
+/// Sign with [`common::RSA_PRIVATE_KEY`] using RS256, but name the key `kid`.
+fn sign_rsa_with_kid(kid: &str) -> String {
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(kid.to_string());
+    let key = EncodingKey::from_rsa_pem(common::RSA_PRIVATE_KEY.as_bytes()).unwrap();
+    encode(&header, &common::claims(), &key).unwrap()
+}
+
+#[test]
+fn verifies_es256_tokens() {
+    let auth = firebase_auth(vec![rsa_jwk(), common::ec_jwk()], all_algorithms());
+
+    let claims: Value = auth.verify(&common::sign_ec(&common::claims())).unwrap();
+
+    assert_eq!(claims["sub"], "user-1");
+}
+
+#[test]
+fn verifies_eddsa_tokens() {
+    let auth = firebase_auth(vec![rsa_jwk(), common::ed_jwk()], all_algorithms());
+
+    let claims: Value = auth.verify(&common::sign_ed(&common::claims())).unwrap();
+
+    assert_eq!(claims["sub"], "user-1");
+}
+
+#[test]
+fn rejects_rs256_header_for_ec_key() {
+    let mut ec_jwk = common::ec_jwk();
+    ec_jwk.as_object_mut().unwrap().remove("alg");
+    let auth = firebase_auth(vec![ec_jwk], all_algorithms());
+
+    let result = auth.verify::<Value>(&sign_rsa_with_kid(common::EC_KID));
+
+    assert!(matches!(
+        result,
+        Err(VerificationError::InvalidKeyAlgorithm)
+    ));
+}
+
+#[test]
+fn rejects_header_alg_that_disagrees_with_the_jwk() {
+    let mut jwk = rsa_jwk();
+    jwk["alg"] = "RS512".into();
+    let auth = firebase_auth(vec![jwk], vec![Algorithm::RS256, Algorithm::RS512]);
+
+    let result = auth.verify::<Value>(&common::sign(&common::claims()));
+
+    assert!(matches!(
+        result,
+        Err(VerificationError::InvalidKeyAlgorithm)
+    ));
+}
+
+#[test]
+fn rejects_header_alg_not_in_configuration() {
+    let auth = firebase_auth(vec![rsa_jwk(), common::ec_jwk()], vec![Algorithm::RS256]);
+
+    let result = auth.verify::<Value>(&common::sign_ec(&common::claims()));
+
+    assert!(matches!(
+        result,
+        Err(VerificationError::InvalidKeyAlgorithm)
+    ));
+}
+
+#[test]
+fn rejects_ec_key_without_coordinates() {
+    for coordinate in ["x", "y"] {
+        let mut ec_jwk = common::ec_jwk();
+        ec_jwk.as_object_mut().unwrap().remove(coordinate);
+        let auth = firebase_auth(vec![ec_jwk], all_algorithms());
+
+        let result = auth.verify::<Value>(&common::sign_ec(&common::claims()));
+
+        assert!(matches!(
+            result,
+            Err(VerificationError::CannotDecodePublicKeys)
+        ));
+    }
+}
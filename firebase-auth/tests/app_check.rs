@@ -0,0 +1,149 @@
+mod common;
+
+use firebase_auth::{
+    get_app_check_configuration, parse_key_document, AppCheck, FirebaseAuth, VerificationError,
+    VerifyOptions,
+};
+use serde_json::{json, Value};
+
+const PROJECT_NUMBER: &str = "123456789";
+const APP_ID: &str = "1:123456789:android:0123456789abcdef";
+
+fn app_check() -> AppCheck {
+    let keys = parse_key_document(&common::jwks().to_string()).unwrap();
+    AppCheck::from_firebase_auth(FirebaseAuth::with_static_keys(
+        get_app_check_configuration(PROJECT_NUMBER),
+        keys,
+    ))
+}
+
+fn app_check_claims(project_number: &str) -> Value {
+    json!({
+        "iss": format!("https://firebaseappcheck.googleapis.com/{}", project_number),
+        "aud": [format!("projects/{}", project_number), "projects/my-project"],
+        "sub": APP_ID,
+        "iat": common::now(),
+        "exp": common::now() + 3600,
+    })
+}
+
+#[test]
+fn verifies_app_check_tokens() {
+    let token = common::sign(&app_check_claims(PROJECT_NUMBER));
+
+    let app_check_token = app_check().verify(&token).unwrap();
+
+    assert_eq!(app_check_token.sub, APP_ID);
+    assert!(app_check_token
+        .aud
+        .contains(&"projects/123456789".to_string()));
+}
+
+#[test]
+fn rejects_tokens_of_other_projects() {
+    let token = common::sign(&app_check_claims("987654321"));
+
+    assert!(matches!(
+        app_check().verify(&token),
+        Err(VerificationError::InvalidToken)
+    ));
+}
+
+#[test]
+fn rejects_firebase_id_tokens() {
+    let token = common::sign(&common::firebase_claims(PROJECT_NUMBER));
+
+    assert!(matches!(
+        app_check().verify(&token),
+        Err(VerificationError::InvalidToken)
+    ));
+}
+
+#[test]
+fn applies_the_verify_options() {
+    let token = common::sign(&app_check_claims(PROJECT_NUMBER));
+    let options = VerifyOptions {
+        required_claims: vec!["app_id".to_owned()],
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        app_check().verify_with(&token, &options),
+        Err(VerificationError::MissingClaim)
+    ));
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_extractor_requires_a_valid_token() {
+    use axum::{
+        extract::{FromRequestParts, OptionalFromRequestParts},
+        http::Request,
+    };
+    use firebase_auth::AppCheckToken;
+
+    let state = app_check();
+    let request = |token: Option<String>| {
+        let mut request = Request::builder();
+        if let Some(token) = token {
+            request = request.header("X-Firebase-AppCheck", token);
+        }
+        request.body(()).unwrap().into_parts().0
+    };
+    let valid = || Some(common::sign(&app_check_claims(PROJECT_NUMBER)));
+    let invalid = || Some(common::sign(&app_check_claims("987654321")));
+
+    let mut parts = request(valid());
+    let token = <AppCheckToken as FromRequestParts<_>>::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .unwrap();
+    assert_eq!(token.sub, APP_ID);
+
+    for token in [None, invalid()] {
+        let mut parts = request(token);
+        assert!(
+            <AppCheckToken as FromRequestParts<_>>::from_request_parts(&mut parts, &state)
+                .await
+                .is_err()
+        );
+    }
+
+    for (token, accepted) in [(valid(), true), (invalid(), false), (None, false)] {
+        let mut parts = request(token);
+        let token =
+            <AppCheckToken as OptionalFromRequestParts<_>>::from_request_parts(&mut parts, &state)
+                .await
+                .unwrap();
+        assert_eq!(token.is_some(), accepted);
+    }
+}
+
+#[cfg(feature = "actix-web")]
+#[actix_web::test]
+async fn actix_extractor_requires_a_valid_token() {
+    use actix_web::{test::TestRequest, web::Data, FromRequest};
+    use firebase_auth::AppCheckToken;
+
+    let app_check = Data::new(app_check());
+    let request = |token: Option<String>| {
+        let mut request = TestRequest::default().app_data(app_check.clone());
+        if let Some(token) = token {
+            request = request.insert_header(("X-Firebase-AppCheck", token));
+        }
+        request.to_http_request()
+    };
+
+    let token = AppCheckToken::extract(&request(Some(common::sign(&app_check_claims(
+        PROJECT_NUMBER,
+    )))))
+    .await
+    .unwrap();
+    assert_eq!(token.sub, APP_ID);
+
+    assert!(AppCheckToken::extract(&request(None)).await.is_err());
+    assert!(Option::<AppCheckToken>::extract(&request(None))
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::time::Duration;
+
+use common::InMemoryKeyFetcher;
+use firebase_auth::{BackoffPolicy, FirebaseAuth, VerificationError};
+use serde_json::Value;
+
+fn backoff_policy(max_staleness: Duration) -> BackoffPolicy {
+    BackoffPolicy {
+        initial_delay: Duration::from_millis(10),
+        multiplier: 2.0,
+        max_delay: Duration::from_millis(20),
+        jitter: 0.0,
+        max_staleness,
+    }
+}
+
+fn verify(auth: &FirebaseAuth) -> Result<Value, VerificationError> {
+    auth.verify(&common::sign(&common::claims()))
+}
+
+#[test]
+fn retry_delay_grows_exponentially_up_to_the_cap() {
+    let policy = BackoffPolicy {
+        initial_delay: Duration::from_secs(1),
+        multiplier: 2.0,
+        max_delay: Duration::from_secs(60),
+        jitter: 0.0,
+        max_staleness: Duration::from_secs(3600),
+    };
+
+    let delays: Vec<u64> = (1..=8).map(|n| policy.retry_delay(n).as_secs()).collect();
+
+    assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+    assert_eq!(policy.retry_delay(u32::MAX), Duration::from_secs(60));
+}
+
+#[test]
+fn retry_delay_jitter_only_shortens_the_delay() {
+    let policy = BackoffPolicy {
+        jitter: 0.5,
+        ..BackoffPolicy::default()
+    };
+
+    for _ in 0..100 {
+        let delay = policy.retry_delay(3);
+        assert!(delay <= Duration::from_secs(4));
+        assert!(delay >= Duration::from_secs(2));
+    }
+}
+
+#[tokio::test]
+async fn keeps_last_good_keys_while_refresh_fails() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .refresh_policy(common::refresh_policy(Duration::from_millis(10)))
+        .backoff_policy(backoff_policy(Duration::from_secs(3600)))
+        .build()
+        .await
+        .unwrap();
+
+    key_fetcher.set_status(503);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert!(key_fetcher.requests() > 3);
+    assert!(verify(&auth).is_ok());
+}
+
+#[tokio::test]
+async fn rejects_tokens_once_keys_are_too_stale() {
+    let mut key_fetcher = InMemoryKeyFetcher::new(200);
+    key_fetcher.cache_control = "no-cache";
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .refresh_policy(common::refresh_policy(Duration::from_millis(10)))
+        .backoff_policy(backoff_policy(Duration::from_millis(100)))
+        .build()
+        .await
+        .unwrap();
+
+    assert!(verify(&auth).is_ok());
+
+    key_fetcher.set_status(503);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert!(matches!(verify(&auth), Err(VerificationError::KeysExpired)));
+
+    key_fetcher.set_status(200);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(verify(&auth).is_ok());
+}
@@ -0,0 +1,225 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use firebase_auth::{FirebaseAuth, PublicKeysError};
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verifies_with_blocking_key_fetch() {
+    let base_url = common::serve_json(|_| vec![("/jwks".to_string(), common::jwks())]).await;
+    let config = common::config(&format!("{}/jwks", base_url));
+
+    // The blocking client runs its own runtime, so keep it off the async test runtime.
+    let status = tokio::task::spawn_blocking(move || {
+        let auth = FirebaseAuth::try_with_configuration_blocking(config).unwrap();
+
+        let claims: Value = auth.verify(&common::sign(&common::claims())).unwrap();
+        assert_eq!(claims["sub"], "user-1");
+
+        auth.status()
+    })
+    .await
+    .unwrap();
+
+    assert!(status.next_refresh.is_some());
+    assert!(!status.refresh_task_running);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fetches_with_the_blocking_client() {
+    let base_url = common::serve_json(|_| vec![("/jwks".to_string(), common::jwks())]).await;
+    let config = common::config(&format!("{}/jwks", base_url));
+
+    let result = tokio::task::spawn_blocking(move || {
+        FirebaseAuth::builder(config)
+            .blocking_client(unreachable_proxy_client())
+            .build_blocking()
+    })
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        result.err(),
+        Some(PublicKeysError::CouldntFetchPublicKeys(_))
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn starts_from_the_key_cache_file() {
+    let base_url = common::serve_json(|_| vec![("/jwks".to_string(), common::jwks())]).await;
+    let config = common::config(&format!("{}/jwks", base_url));
+    let path = std::env::temp_dir().join(format!(
+        "firebase-auth-blocking-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    tokio::task::spawn_blocking(move || {
+        FirebaseAuth::builder(config.clone())
+            .key_cache_file(&path)
+            .build_blocking()
+            .unwrap();
+        assert!(path.exists());
+
+        // The keys come from the file, the client cannot reach the server.
+        let auth = FirebaseAuth::builder(config)
+            .key_cache_file(&path)
+            .blocking_client(unreachable_proxy_client())
+            .build_blocking()
+            .unwrap();
+        let claims: Value = auth.verify(&common::sign(&common::claims())).unwrap();
+        assert_eq!(claims["sub"], "user-1");
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn rejects_a_custom_key_fetcher() {
+    let result = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(common::InMemoryKeyFetcher::new(200))
+        .build_blocking();
+
+    assert!(matches!(
+        result.err(),
+        Some(PublicKeysError::UnsupportedKeyFetcher)
+    ));
+}
+
+fn unreachable_proxy_client() -> reqwest::blocking::Client {
+    reqwest::blocking::Client::builder()
+        .proxy(reqwest::Proxy::all("http://127.0.0.1:9").unwrap())
+        .build()
+        .unwrap()
+}
+
+/// Lazy refreshes on `verify`, against a server whose keys and status can change.
+#[cfg(feature = "testing")]
+mod lazy_refresh {
+    use std::{
+        sync::OnceLock,
+        thread,
+        time::{Duration, Instant, SystemTime},
+    };
+
+    use firebase_auth::{
+        testing::{MockJwksServer, TestIssuer},
+        BackoffPolicy, FirebaseAuth, VerificationError,
+    };
+    use serde_json::Value;
+
+    use super::common;
+
+    const PROJECT_ID: &str = "my-project";
+    const REFRESH_DELAY: Duration = Duration::from_millis(100);
+
+    fn issuers() -> &'static [TestIssuer; 2] {
+        static ISSUERS: OnceLock<[TestIssuer; 2]> = OnceLock::new();
+        ISSUERS.get_or_init(|| [TestIssuer::new(PROJECT_ID), TestIssuer::new(PROJECT_ID)])
+    }
+
+    /// Build on a blocking thread, as the blocking client must not run on the async runtime.
+    async fn build(server: &MockJwksServer, backoff_policy: BackoffPolicy) -> FirebaseAuth {
+        let config = server.configuration(PROJECT_ID);
+        tokio::task::spawn_blocking(move || {
+            FirebaseAuth::builder(config)
+                .refresh_policy(common::refresh_policy(REFRESH_DELAY))
+                .backoff_policy(backoff_policy)
+                .build_blocking()
+                .unwrap()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn refreshes_on_verify_once_the_keys_are_due() {
+        let [current, next] = issuers();
+        let server = MockJwksServer::start(vec![current.jwk()]).await;
+        let auth = build(&server, BackoffPolicy::default()).await;
+        server.set_keys(vec![next.jwk()]);
+        let next_token = next.token("user-1").sign();
+
+        let requests = tokio::task::spawn_blocking(move || {
+            assert!(matches!(
+                auth.verify::<Value>(&next_token),
+                Err(VerificationError::NotfoundMatchKid)
+            ));
+            let requests_before_due = server.requests();
+
+            thread::sleep(REFRESH_DELAY);
+            let claims: Value = auth.verify(&next_token).unwrap();
+            assert_eq!(claims["sub"], "user-1");
+            assert_eq!(auth.status().kids, vec![next.kid().to_string()]);
+
+            (requests_before_due, server.requests())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(requests, (1, 2));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_one_caller_refreshes() {
+        let [current, _] = issuers();
+        let server = MockJwksServer::start(vec![current.jwk()]).await;
+        let auth = build(&server, BackoffPolicy::default()).await;
+        server.set_delay(Duration::from_millis(200));
+        let token = current.token("user-1").sign();
+
+        let (requests, waited) = tokio::task::spawn_blocking(move || {
+            thread::sleep(REFRESH_DELAY);
+            let waited: Vec<Duration> = thread::scope(|scope| {
+                let callers: Vec<_> = (0..8)
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let started = Instant::now();
+                            auth.verify::<Value>(&token).unwrap();
+                            started.elapsed()
+                        })
+                    })
+                    .collect();
+                callers.into_iter().map(|c| c.join().unwrap()).collect()
+            });
+            (server.requests(), waited)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(requests, 2);
+        // Only the refreshing caller waits for the slow server, the others use the current keys.
+        let slow = waited.iter().filter(|w| **w >= Duration::from_millis(200));
+        assert_eq!(slow.count(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn backs_off_after_a_failed_refresh() {
+        let [current, _] = issuers();
+        let server = MockJwksServer::start(vec![current.jwk()]).await;
+        let backoff_policy = BackoffPolicy {
+            initial_delay: Duration::from_secs(60),
+            jitter: 0.0,
+            ..Default::default()
+        };
+        let auth = build(&server, backoff_policy).await;
+        server.set_status(503);
+        let token = current.token("user-1").sign();
+
+        let (requests, status) = tokio::task::spawn_blocking(move || {
+            thread::sleep(REFRESH_DELAY);
+            for _ in 0..3 {
+                // The last good keys stay in use while the refresh is retried.
+                assert!(auth.verify::<Value>(&token).is_ok());
+            }
+            (server.requests(), auth.status())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(requests, 2);
+        assert_eq!(status.consecutive_failures, 1);
+        assert!(status.next_refresh.unwrap() > SystemTime::now() + Duration::from_secs(50));
+    }
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use firebase_auth::{JwtVerifier, PublicKeysError, VerificationError};
+use serde_json::{json, Value};
+
+async fn serve_issuer(issuer_suffix: &str) -> String {
+    let issuer_suffix = issuer_suffix.to_string();
+    common::serve_json(move |base_url| {
+        vec![
+            (
+                "/.well-known/openid-configuration".to_string(),
+                json!({
+                    "issuer": format!("{}{}", base_url, issuer_suffix),
+                    "jwks_uri": format!("{}/.well-known/jwks.json", base_url),
+                    "id_token_signing_alg_values_supported": ["RS256", "HS256"],
+                }),
+            ),
+            ("/.well-known/jwks.json".to_string(), common::jwks()),
+        ]
+    })
+    .await
+}
+
+fn claims(issuer: &str, audience: &str) -> Value {
+    json!({
+        "iss": issuer,
+        "aud": audience,
+        "sub": "auth0|partner-user",
+        "iat": common::now(),
+        "exp": common::now() + 3600,
+    })
+}
+
+#[tokio::test]
+async fn verifies_token_signed_by_discovered_keys() {
+    let base_url = serve_issuer("/").await;
+    let verifier = JwtVerifier::from_discovery(&base_url, "partner-api")
+        .await
+        .unwrap();
+
+    let token = common::sign(&claims(&format!("{}/", base_url), "partner-api"));
+    let claims: Value = verifier.verify(&token).unwrap();
+
+    assert_eq!(claims["sub"], "auth0|partner-user");
+}
+
+#[tokio::test]
+async fn rejects_token_for_other_audience() {
+    let base_url = serve_issuer("/").await;
+    let verifier = JwtVerifier::from_discovery(&base_url, "partner-api")
+        .await
+        .unwrap();
+
+    let token = common::sign(&claims(&format!("{}/", base_url), "other-api"));
+    let result: Result<Value, VerificationError> = verifier.verify(&token);
+
+    assert!(matches!(result, Err(VerificationError::InvalidToken)));
+}
+
+#[tokio::test]
+async fn rejects_token_from_other_issuer() {
+    let base_url = serve_issuer("/").await;
+    let verifier = JwtVerifier::from_discovery(&base_url, "partner-api")
+        .await
+        .unwrap();
+
+    let token = common::sign(&claims("https://evil.example.com/", "partner-api"));
+    let result: Result<Value, VerificationError> = verifier.verify(&token);
+
+    assert!(matches!(result, Err(VerificationError::InvalidToken)));
+}
+
+#[tokio::test]
+async fn rejects_discovery_document_for_other_issuer() {
+    let base_url = serve_issuer("/tenant").await;
+    let result = JwtVerifier::from_discovery(&base_url, "partner-api").await;
+
+    assert!(matches!(
+        result.err(),
+        Some(PublicKeysError::DiscoveryIssuerMismatch)
+    ));
+}
+
+#[tokio::test]
+async fn ignores_symmetric_algorithms_from_discovery() {
+    let base_url = serve_issuer("").await;
+    let verifier = JwtVerifier::from_discovery(&base_url, "partner-api")
+        .await
+        .unwrap();
+
+    let header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::HS256);
+    let token = jsonwebtoken::encode(
+        &header,
+        &claims(&base_url, "partner-api"),
+        &jsonwebtoken::EncodingKey::from_secret(common::RSA_N.as_bytes()),
+    )
+    .unwrap();
+    let result: Result<Value, VerificationError> = verifier.verify(&token);
+
+    assert!(matches!(
+        result,
+        Err(VerificationError::InvalidKeyAlgorithm)
+    ));
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_extractor_uses_the_jwt_verifier_next_to_firebase_auth() {
+    use axum::{
+        extract::{FromRef, FromRequestParts},
+        http::Request,
+    };
+    use firebase_auth::{FirebaseAuthState, FirebaseUser, JwtClaims};
+
+    #[derive(Clone)]
+    struct AppState {
+        firebase_auth: FirebaseAuthState,
+        partner: JwtVerifier,
+    }
+
+    impl FromRef<AppState> for FirebaseAuthState {
+        fn from_ref(state: &AppState) -> Self {
+            state.firebase_auth.clone()
+        }
+    }
+
+    impl FromRef<AppState> for JwtVerifier {
+        fn from_ref(state: &AppState) -> Self {
+            state.partner.clone()
+        }
+    }
+
+    let base_url = serve_issuer("/").await;
+    let state = AppState {
+        firebase_auth: FirebaseAuthState::new(common::firebase_auth()),
+        partner: JwtVerifier::from_discovery(&base_url, "partner-api")
+            .await
+            .unwrap(),
+    };
+    let parts = |token: String| {
+        Request::builder()
+            .header("Authorization", format!("Bearer {}", token))
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    };
+
+    let partner_token = common::sign(&claims(&format!("{}/", base_url), "partner-api"));
+    let JwtClaims(partner) =
+        JwtClaims::<Value>::from_request_parts(&mut parts(partner_token.clone()), &state)
+            .await
+            .ok()
+            .unwrap();
+    assert_eq!(partner["sub"], "auth0|partner-user");
+    assert!(
+        FirebaseUser::from_request_parts(&mut parts(partner_token), &state)
+            .await
+            .is_err()
+    );
+
+    let firebase_token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let user = FirebaseUser::from_request_parts(&mut parts(firebase_token.clone()), &state)
+        .await
+        .ok()
+        .unwrap();
+    assert_eq!(user.sub, "user-1");
+    assert!(
+        JwtClaims::<Value>::from_request_parts(&mut parts(firebase_token), &state)
+            .await
+            .is_err()
+    );
+}
+
+#[cfg(feature = "actix-web")]
+#[actix_web::test]
+async fn actix_extractor_uses_the_jwt_verifier_next_to_firebase_auth() {
+    use actix_web::{test::TestRequest, web::Data, FromRequest};
+    use firebase_auth::{FirebaseUser, JwtClaims};
+
+    let base_url = serve_issuer("/").await;
+    let partner = Data::new(
+        JwtVerifier::from_discovery(&base_url, "partner-api")
+            .await
+            .unwrap(),
+    );
+    let firebase_auth = Data::new(common::firebase_auth());
+    let request = |token: String| {
+        TestRequest::default()
+            .app_data(firebase_auth.clone())
+            .app_data(partner.clone())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_http_request()
+    };
+
+    let partner_token = common::sign(&claims(&format!("{}/", base_url), "partner-api"));
+    let JwtClaims(claims) = JwtClaims::<Value>::extract(&request(partner_token.clone()))
+        .await
+        .unwrap();
+    assert_eq!(claims["sub"], "auth0|partner-user");
+    assert!(FirebaseUser::extract(&request(partner_token))
+        .await
+        .is_err());
+
+    let firebase_token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let user = FirebaseUser::extract(&request(firebase_token.clone()))
+        .await
+        .unwrap();
+    assert_eq!(user.sub, "user-1");
+    let error = JwtClaims::<Value>::extract(&request(firebase_token))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.as_response_error().status_code(),
+        actix_web::http::StatusCode::UNAUTHORIZED
+    );
+}
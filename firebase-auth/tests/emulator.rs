@@ -0,0 +1,46 @@
+//! Sets `FIREBASE_AUTH_EMULATOR_HOST` for the whole process, so it runs in its own test binary.
+
+mod common;
+
+use base64::{prelude::BASE64_STANDARD_NO_PAD, Engine};
+use firebase_auth::{
+    get_configuration, get_google_oidc_configuration, get_iap_configuration, parse_key_document,
+    FirebaseAuth, JwkConfiguration,
+};
+use serde_json::{json, Value};
+
+/// A token with an unsigned payload as accepted by the Firebase Auth emulator.
+fn unsigned_token(claims: &Value) -> String {
+    format!(
+        "{}.{}.",
+        BASE64_STANDARD_NO_PAD.encode(r#"{"alg":"none"}"#),
+        BASE64_STANDARD_NO_PAD.encode(claims.to_string()),
+    )
+}
+
+fn verify(config: JwkConfiguration, claims: &Value) -> bool {
+    let keys = parse_key_document(&common::jwks().to_string()).unwrap();
+    FirebaseAuth::with_static_keys(config, keys)
+        .verify::<Value>(&unsigned_token(claims))
+        .is_ok()
+}
+
+#[test]
+fn accepts_unsigned_tokens_only_where_the_emulator_is_allowed() {
+    std::env::set_var("FIREBASE_AUTH_EMULATOR_HOST", "127.0.0.1:9099");
+
+    let claims = json!({
+        "iss": "https://accounts.google.com",
+        "aud": "audience",
+        "sub": "user-1",
+        "iat": common::now(),
+        "exp": common::now() + 3600,
+    });
+    assert!(!verify(get_iap_configuration("audience"), &claims));
+    assert!(!verify(get_google_oidc_configuration("audience"), &claims));
+
+    assert!(verify(
+        get_configuration(common::AUDIENCE),
+        &common::firebase_claims(common::AUDIENCE)
+    ));
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use firebase_auth::{
+    get_google_oidc_configuration, get_iap_configuration, parse_key_document, FirebaseAuth,
+    GoogleOidcAuth, IapAuth, VerificationError,
+};
+use serde_json::{json, Value};
+
+const IAP_AUDIENCE: &str = "/projects/123456789/apps/my-project";
+const SERVICE_URL: &str = "https://my-service.a.run.app";
+
+fn iap_auth() -> IapAuth {
+    let keys = parse_key_document(&json!({ "keys": [common::ec_jwk()] }).to_string()).unwrap();
+    IapAuth::from_firebase_auth(FirebaseAuth::with_static_keys(
+        get_iap_configuration(IAP_AUDIENCE),
+        keys,
+    ))
+}
+
+fn google_oidc_auth() -> GoogleOidcAuth {
+    let keys = parse_key_document(&common::jwks().to_string()).unwrap();
+    GoogleOidcAuth::from_firebase_auth(FirebaseAuth::with_static_keys(
+        get_google_oidc_configuration(SERVICE_URL),
+        keys,
+    ))
+}
+
+fn iap_claims() -> Value {
+    json!({
+        "iss": "https://cloud.google.com/iap",
+        "aud": IAP_AUDIENCE,
+        "sub": "accounts.google.com:1234",
+        "iat": common::now(),
+        "exp": common::now() + 600,
+        "email": "user@example.com",
+    })
+}
+
+fn google_claims(issuer: &str) -> Value {
+    json!({
+        "iss": issuer,
+        "aud": SERVICE_URL,
+        "sub": "1234",
+        "iat": common::now(),
+        "exp": common::now() + 3600,
+        "email": "caller@my-project.iam.gserviceaccount.com",
+    })
+}
+
+#[test]
+fn verifies_iap_assertions() {
+    let user = iap_auth().verify(&common::sign_ec(&iap_claims())).unwrap();
+
+    assert_eq!(user.email, "user@example.com");
+    assert_eq!(user.aud, IAP_AUDIENCE);
+}
+
+#[test]
+fn accepts_both_google_issuer_forms() {
+    for issuer in ["https://accounts.google.com", "accounts.google.com"] {
+        let user = google_oidc_auth()
+            .verify(&common::sign(&google_claims(issuer)))
+            .unwrap();
+        assert_eq!(user.iss, issuer);
+    }
+
+    assert!(matches!(
+        google_oidc_auth().verify(&common::sign(&google_claims("https://issuer.example.com"))),
+        Err(VerificationError::InvalidToken)
+    ));
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_extractors_use_their_own_state() {
+    use axum::{
+        body::to_bytes,
+        extract::{FromRef, FromRequestParts},
+        http::{request::Parts, Request, StatusCode},
+        response::IntoResponse,
+    };
+    use firebase_auth::{FirebaseAuthState, FirebaseUser, GoogleUser, IapUser};
+
+    #[derive(Clone)]
+    struct AppState {
+        firebase_auth: FirebaseAuthState,
+        iap_auth: IapAuth,
+        google_auth: GoogleOidcAuth,
+    }
+
+    impl FromRef<AppState> for FirebaseAuthState {
+        fn from_ref(state: &AppState) -> Self {
+            state.firebase_auth.clone()
+        }
+    }
+
+    impl FromRef<AppState> for IapAuth {
+        fn from_ref(state: &AppState) -> Self {
+            state.iap_auth.clone()
+        }
+    }
+
+    impl FromRef<AppState> for GoogleOidcAuth {
+        fn from_ref(state: &AppState) -> Self {
+            state.google_auth.clone()
+        }
+    }
+
+    let state = AppState {
+        firebase_auth: FirebaseAuthState::new(
+            FirebaseAuth::from_static_keys(common::AUDIENCE, &common::jwks().to_string()).unwrap(),
+        ),
+        iap_auth: iap_auth(),
+        google_auth: google_oidc_auth(),
+    };
+    let request = |header: Option<(&str, String)>| -> Parts {
+        let mut request = Request::builder();
+        if let Some((name, value)) = header {
+            request = request.header(name, value);
+        }
+        request.body(()).unwrap().into_parts().0
+    };
+
+    let assertion = common::sign_ec(&iap_claims());
+    let mut parts = request(Some(("x-goog-iap-jwt-assertion", assertion)));
+    let user = IapUser::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .unwrap();
+    assert_eq!(user.email, "user@example.com");
+
+    let mut parts = request(None);
+    let response = IapUser::from_request_parts(&mut parts, &state)
+        .await
+        .err()
+        .unwrap()
+        .into_response();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&body[..], b"Missing IAP Jwt Assertion");
+
+    let token = common::sign(&google_claims("accounts.google.com"));
+    let mut parts = request(Some(("Authorization", format!("Bearer {}", token))));
+    assert!(GoogleUser::from_request_parts(&mut parts, &state)
+        .await
+        .is_ok());
+    // The Google token is not a Firebase ID token of the project.
+    let mut parts = request(Some(("Authorization", format!("Bearer {}", token))));
+    assert!(FirebaseUser::from_request_parts(&mut parts, &state)
+        .await
+        .is_err());
+}
+
+#[cfg(feature = "actix-web")]
+#[actix_web::test]
+async fn actix_iap_extractor_reads_the_assertion_header() {
+    use actix_web::{test::TestRequest, web::Data, FromRequest};
+    use firebase_auth::IapUser;
+
+    let iap_auth = Data::new(iap_auth());
+
+    let request = TestRequest::default()
+        .app_data(iap_auth.clone())
+        .insert_header(("x-goog-iap-jwt-assertion", common::sign_ec(&iap_claims())))
+        .to_http_request();
+    let user = IapUser::extract(&request).await.unwrap();
+    assert_eq!(user.email, "user@example.com");
+
+    let request = TestRequest::default().app_data(iap_auth).to_http_request();
+    let error = IapUser::extract(&request).await.err().unwrap();
+    assert_eq!(error.to_string(), "Missing IAP Jwt Assertion");
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use firebase_auth::{FirebaseAuth, TokenReport, VerificationError};
+
+fn inspect(token: &str) -> TokenReport {
+    let document = common::jwks().to_string();
+    let auth = FirebaseAuth::from_static_keys(common::AUDIENCE, &document).unwrap();
+    auth.inspect(token)
+}
+
+fn failed_checks(report: &TokenReport) -> Vec<&str> {
+    report
+        .checks
+        .iter()
+        .filter(|check| !check.passed)
+        .map(|check| check.name)
+        .collect()
+}
+
+#[test]
+fn reports_a_valid_token() {
+    let report = inspect(&common::sign(&common::firebase_claims(common::AUDIENCE)));
+
+    assert!(report.result.is_ok());
+    assert!(failed_checks(&report).is_empty());
+    assert_eq!(report.kid.as_deref(), Some(common::KID));
+    assert_eq!(report.header.unwrap()["alg"], "RS256");
+    assert_eq!(report.claims.unwrap()["sub"], "user-1");
+}
+
+#[test]
+fn applies_the_leeway_to_the_expiry() {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["exp"] = (common::now() - 30).into();
+
+    let report = inspect(&common::sign(&claims));
+
+    assert!(report.result.is_ok());
+    assert!(failed_checks(&report).is_empty());
+    let expiry = report.checks.iter().find(|c| c.name == "expiry").unwrap();
+    assert!(expiry.detail.ends_with("with 60s leeway"));
+}
+
+#[test]
+fn lists_only_the_checks_of_the_verification() {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["iat"] = (common::now() + 3600).into();
+
+    let report = inspect(&common::sign(&claims));
+
+    assert!(report.result.is_ok());
+    assert!(failed_checks(&report).is_empty());
+}
+
+#[test]
+fn reports_the_failing_checks() {
+    let mut claims = common::firebase_claims("other-project");
+    claims["exp"] = (common::now() - 120).into();
+
+    let report = inspect(&common::sign(&claims));
+
+    assert!(matches!(
+        report.result,
+        Err(VerificationError::InvalidToken)
+    ));
+    assert_eq!(
+        failed_checks(&report),
+        vec!["issuer", "audience", "expiry", "verification"]
+    );
+}
+
+#[test]
+fn reports_undecodable_tokens() {
+    let report = inspect("not-a-token");
+
+    assert!(report.result.is_err());
+    assert!(report.header.is_none());
+    assert_eq!(
+        failed_checks(&report),
+        vec!["header", "claims", "verification"]
+    );
+}
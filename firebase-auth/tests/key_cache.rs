@@ -0,0 +1,92 @@
+mod common;
+
+use std::{fs, path::PathBuf};
+
+use common::InMemoryKeyFetcher;
+use firebase_auth::FirebaseAuth;
+use serde_json::{json, Value};
+
+const JWK_URL: &str = "memory://jwks";
+
+fn cache_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "firebase-auth-{}-{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+async fn build(path: &PathBuf, key_fetcher: &InMemoryKeyFetcher) -> Option<FirebaseAuth> {
+    FirebaseAuth::builder(common::config(JWK_URL))
+        .key_fetcher(key_fetcher.clone())
+        .key_cache_file(path)
+        .build()
+        .await
+        .ok()
+}
+
+fn assert_verifies(auth: &FirebaseAuth) {
+    let claims: Value = auth.verify(&common::sign(&common::claims())).unwrap();
+    assert_eq!(claims["sub"], "user-1");
+}
+
+#[tokio::test]
+async fn starts_from_fresh_cache_without_fetching() {
+    let path = cache_path("fresh");
+
+    let online = InMemoryKeyFetcher::new(200);
+    build(&path, &online).await.unwrap();
+    assert_eq!(online.requests(), 1);
+    assert!(path.exists());
+
+    let offline = InMemoryKeyFetcher::new(503);
+    let auth = build(&path, &offline).await.unwrap();
+
+    assert_eq!(offline.requests(), 0);
+    assert_verifies(&auth);
+}
+
+#[tokio::test]
+async fn fetches_when_cache_is_corrupt() {
+    let path = cache_path("corrupt");
+    fs::write(&path, "{ not json").unwrap();
+
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = build(&path, &key_fetcher).await.unwrap();
+
+    assert_eq!(key_fetcher.requests(), 1);
+    assert_verifies(&auth);
+}
+
+#[tokio::test]
+async fn fetches_when_cache_is_stale() {
+    let path = cache_path("stale");
+    let stale = json!({
+        "jwk_url": JWK_URL,
+        "expires_at": common::now() - 1,
+        "keys": common::jwks()["keys"],
+    });
+    fs::write(&path, stale.to_string()).unwrap();
+
+    let key_fetcher = InMemoryKeyFetcher::new(503);
+    assert!(build(&path, &key_fetcher).await.is_none());
+    assert_eq!(key_fetcher.requests(), 1);
+}
+
+#[tokio::test]
+async fn ignores_cache_of_other_key_url() {
+    let path = cache_path("other-url");
+    let other = json!({
+        "jwk_url": "memory://other",
+        "expires_at": common::now() + 3600,
+        "keys": common::jwks()["keys"],
+    });
+    fs::write(&path, other.to_string()).unwrap();
+
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    build(&path, &key_fetcher).await.unwrap();
+
+    assert_eq!(key_fetcher.requests(), 1);
+}
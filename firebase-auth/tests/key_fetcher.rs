@@ -0,0 +1,34 @@
+mod common;
+
+use common::InMemoryKeyFetcher;
+use firebase_auth::{FirebaseAuth, PublicKeysError};
+use serde_json::Value;
+
+#[tokio::test]
+async fn verifies_with_keys_from_custom_fetcher() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let token = common::sign(&common::claims());
+    let claims: Value = auth.verify(&token).unwrap();
+
+    assert_eq!(claims["sub"], "user-1");
+    assert_eq!(key_fetcher.requests(), 1);
+}
+
+#[tokio::test]
+async fn rejects_unsuccessful_key_response() {
+    let result = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(InMemoryKeyFetcher::new(503))
+        .build()
+        .await;
+
+    assert!(matches!(
+        result.err(),
+        Some(PublicKeysError::UnexpectedStatus(503))
+    ));
+}
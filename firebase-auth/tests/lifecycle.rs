@@ -0,0 +1,91 @@
+mod common;
+
+use std::time::Duration;
+
+use common::InMemoryKeyFetcher;
+use firebase_auth::{FirebaseAuth, KeyFetchResponse, KeyFetcher, PublicKeysError};
+use futures::future::BoxFuture;
+use tokio::time::sleep;
+
+/// Serves the keys once, then panics on every refresh.
+struct PanickingKeyFetcher(InMemoryKeyFetcher);
+
+impl KeyFetcher for PanickingKeyFetcher {
+    fn fetch<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<KeyFetchResponse, PublicKeysError>> {
+        if self.0.requests() > 0 {
+            panic!("key fetcher failed");
+        }
+        self.0.fetch(url)
+    }
+}
+
+async fn build(key_fetcher: &InMemoryKeyFetcher) -> FirebaseAuth {
+    FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .refresh_policy(common::refresh_policy(Duration::from_millis(10)))
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn dropping_a_clone_keeps_refreshing() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = build(&key_fetcher).await;
+
+    drop(auth.clone());
+    let requests = key_fetcher.requests();
+    sleep(Duration::from_millis(100)).await;
+
+    assert!(key_fetcher.requests() > requests);
+    assert!(auth.status().refresh_task_running);
+}
+
+#[tokio::test]
+async fn dropping_the_last_clone_stops_refreshing() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = build(&key_fetcher).await;
+
+    drop(auth);
+    sleep(Duration::from_millis(20)).await;
+    let requests = key_fetcher.requests();
+    sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(key_fetcher.requests(), requests);
+}
+
+#[tokio::test]
+async fn shutdown_stops_refreshing_for_all_clones() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = build(&key_fetcher).await;
+    let clone = auth.clone();
+
+    auth.shutdown().await;
+    let requests = key_fetcher.requests();
+    sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(key_fetcher.requests(), requests);
+    assert!(!clone.status().refresh_task_running);
+    assert!(clone
+        .verify::<serde_json::Value>(&common::sign(&common::claims()))
+        .is_ok());
+}
+
+#[tokio::test]
+async fn reports_a_refresh_task_that_died() {
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(PanickingKeyFetcher(InMemoryKeyFetcher::new(200)))
+        .refresh_policy(common::refresh_policy(Duration::from_millis(10)))
+        .build()
+        .await
+        .unwrap();
+    assert!(auth.status().refresh_task_running);
+
+    sleep(Duration::from_millis(100)).await;
+
+    assert!(!auth.status().refresh_task_running);
+    auth.shutdown().await;
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use firebase_auth::{FirebaseAuth, VerificationError};
+use metrics_util::{
+    debugging::{DebugValue, DebuggingRecorder},
+    MetricKind,
+};
+use serde_json::Value;
+
+#[test]
+fn counts_verifications_by_outcome() {
+    let document = common::jwks().to_string();
+    let auth = FirebaseAuth::from_static_keys(common::AUDIENCE, &document).unwrap();
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let other_project_token = common::sign(&common::firebase_claims("other-project"));
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let _: Value = auth.verify(&token).unwrap();
+        let result: Result<Value, _> = auth.verify(&other_project_token);
+        assert!(matches!(result, Err(VerificationError::InvalidToken)));
+    });
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let count = |outcome: &str| {
+        snapshot.iter().find_map(|(key, _, _, value)| {
+            let key = key.key();
+            let matches = key.name() == "firebase_auth_verifications_total"
+                && key.labels().any(|label| label.value() == outcome);
+            match value {
+                DebugValue::Counter(count) if matches => Some(*count),
+                _ => None,
+            }
+        })
+    };
+    assert_eq!(count("ok"), Some(1));
+    assert_eq!(count("InvalidToken"), Some(1));
+
+    let latencies = snapshot.iter().find_map(|(key, _, _, value)| match value {
+        DebugValue::Histogram(values)
+            if key.kind() == MetricKind::Histogram
+                && key.key().name() == "firebase_auth_verification_duration_seconds" =>
+        {
+            Some(values.len())
+        }
+        _ => None,
+    });
+    assert_eq!(latencies, Some(2));
+}
+
+#[test]
+fn counts_verification_cache_hits_and_misses() {
+    let document = common::jwks().to_string();
+    let auth = FirebaseAuth::from_static_keys(common::AUDIENCE, &document)
+        .unwrap()
+        .with_verification_cache(10);
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        for _ in 0..3 {
+            let _: Value = auth.verify(&token).unwrap();
+        }
+    });
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let count = |name: &str| {
+        snapshot.iter().find_map(|(key, _, _, value)| match value {
+            DebugValue::Counter(count) if key.key().name() == name => Some(*count),
+            _ => None,
+        })
+    };
+    assert_eq!(
+        count("firebase_auth_verification_cache_hits_total"),
+        Some(2)
+    );
+    assert_eq!(
+        count("firebase_auth_verification_cache_misses_total"),
+        Some(1)
+    );
+}
+
+#[test]
+fn labels_metrics_with_the_jwk_url() {
+    let keys: Vec<firebase_auth::JwkKey> =
+        serde_json::from_value(common::jwks()["keys"].clone()).unwrap();
+    let first = FirebaseAuth::with_static_keys(common::config("https://first/jwks"), keys.clone());
+    let second = FirebaseAuth::with_static_keys(common::config("https://second/jwks"), keys);
+    let token = common::sign(&common::claims());
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let _: Value = first.verify(&token).unwrap();
+        let _: Value = second.verify(&token).unwrap();
+        let _: Value = second.verify(&token).unwrap();
+    });
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let count = |jwk_url: &str| {
+        snapshot.iter().find_map(|(key, _, _, value)| {
+            let key = key.key();
+            let matches = key.name() == "firebase_auth_verifications_total"
+                && key
+                    .labels()
+                    .any(|label| label.key() == "jwk_url" && label.value() == jwk_url);
+            match value {
+                DebugValue::Counter(count) if matches => Some(*count),
+                _ => None,
+            }
+        })
+    };
+    assert_eq!(count("https://first/jwks"), Some(1));
+    assert_eq!(count("https://second/jwks"), Some(2));
+}
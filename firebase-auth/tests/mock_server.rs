@@ -0,0 +1,170 @@
+#![cfg(feature = "testing")]
+
+use std::{
+    sync::OnceLock,
+    time::{Duration, SystemTime},
+};
+
+use firebase_auth::{
+    testing::{MockJwksServer, TestIssuer},
+    FirebaseAuth, PublicKeysError, RefreshEvent, RefreshPolicy, ReqwestKeyFetcher,
+    VerificationError,
+};
+use serde_json::Value;
+use tokio::sync::watch;
+
+const PROJECT_ID: &str = "my-project";
+
+fn issuers() -> &'static [TestIssuer; 2] {
+    static ISSUERS: OnceLock<[TestIssuer; 2]> = OnceLock::new();
+    ISSUERS.get_or_init(|| [TestIssuer::new(PROJECT_ID), TestIssuer::new(PROJECT_ID)])
+}
+
+fn refresh_policy(min_delay: Duration, max_delay: Duration) -> RefreshPolicy {
+    RefreshPolicy {
+        fraction: 1.0,
+        jitter: 0.0,
+        min_delay,
+        max_delay,
+    }
+}
+
+async fn build(server: &MockJwksServer, refresh_policy: RefreshPolicy) -> FirebaseAuth {
+    FirebaseAuth::builder(server.configuration(PROJECT_ID))
+        .refresh_policy(refresh_policy)
+        .build()
+        .await
+        .unwrap()
+}
+
+async fn next_event<F>(events: &mut watch::Receiver<RefreshEvent>, matches: F)
+where
+    F: Fn(&RefreshEvent) -> bool,
+{
+    loop {
+        events.changed().await.unwrap();
+        if matches(&events.borrow_and_update()) {
+            return;
+        }
+    }
+}
+
+#[tokio::test]
+async fn picks_up_rotated_keys_on_refresh() {
+    let [current, next] = issuers();
+    let server = MockJwksServer::start(vec![current.jwk()]).await;
+    let auth = build(
+        &server,
+        refresh_policy(Duration::from_millis(50), Duration::from_millis(50)),
+    )
+    .await;
+    let mut events = auth.subscribe();
+
+    let next_token = next.token("user-1").sign();
+    assert!(matches!(
+        auth.verify::<Value>(&next_token),
+        Err(VerificationError::NotfoundMatchKid)
+    ));
+
+    server.set_keys(vec![current.jwk(), next.jwk()]);
+    next_event(
+        &mut events,
+        |event| matches!(event, RefreshEvent::Refreshed { kids, .. } if kids.len() == 2),
+    )
+    .await;
+
+    assert!(auth.verify::<Value>(&next_token).is_ok());
+    assert!(auth
+        .verify::<Value>(&current.token("user-1").sign())
+        .is_ok());
+}
+
+#[tokio::test]
+async fn keeps_the_keys_while_the_server_fails() {
+    let [current, _] = issuers();
+    let server = MockJwksServer::start(vec![current.jwk()]).await;
+    let auth = build(
+        &server,
+        refresh_policy(Duration::from_millis(20), Duration::from_millis(20)),
+    )
+    .await;
+    let mut events = auth.subscribe();
+
+    server.set_status(500);
+    next_event(&mut events, |event| {
+        matches!(event, RefreshEvent::Failed { .. })
+    })
+    .await;
+
+    assert!(auth.status().consecutive_failures >= 1);
+    assert!(auth
+        .verify::<Value>(&current.token("user-1").sign())
+        .is_ok());
+
+    server.set_status(200);
+    next_event(&mut events, |event| {
+        matches!(event, RefreshEvent::Refreshed { .. })
+    })
+    .await;
+    assert_eq!(auth.status().consecutive_failures, 0);
+}
+
+#[tokio::test]
+async fn schedules_the_refresh_from_cache_control() {
+    let [current, _] = issuers();
+    let server = MockJwksServer::start(vec![current.jwk()]).await;
+    server.set_header("Cache-Control", "public, max-age=120");
+
+    let auth = build(
+        &server,
+        refresh_policy(Duration::ZERO, Duration::from_secs(3600)),
+    )
+    .await;
+
+    let next_refresh = auth
+        .status()
+        .next_refresh
+        .unwrap()
+        .duration_since(SystemTime::now())
+        .unwrap();
+    assert!(next_refresh > Duration::from_secs(110));
+    assert!(next_refresh <= Duration::from_secs(120));
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn refreshes_no_store_responses_after_the_minimum_delay() {
+    let [current, _] = issuers();
+    let server = MockJwksServer::start(vec![current.jwk()]).await;
+    server.set_header("Cache-Control", "no-store");
+
+    let _auth = build(
+        &server,
+        refresh_policy(Duration::from_millis(20), Duration::from_secs(3600)),
+    )
+    .await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert!(server.requests() > 2);
+}
+
+#[tokio::test]
+async fn times_out_on_slow_responses() {
+    let [current, _] = issuers();
+    let server = MockJwksServer::start(vec![current.jwk()]).await;
+    server.set_delay(Duration::from_millis(500));
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(50))
+        .build()
+        .unwrap();
+
+    let result = FirebaseAuth::builder(server.configuration(PROJECT_ID))
+        .key_fetcher(ReqwestKeyFetcher::from(client))
+        .build()
+        .await;
+
+    assert!(matches!(
+        result.err(),
+        Some(PublicKeysError::CouldntFetchPublicKeys(_))
+    ));
+}
@@ -0,0 +1,101 @@
+use std::time::{Duration, SystemTime};
+
+use firebase_auth::{freshness_lifetime, KeyFetchResponse, RefreshPolicy};
+
+fn response(headers: &[(&str, &str)]) -> KeyFetchResponse {
+    KeyFetchResponse {
+        status: 200,
+        headers: headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        body: vec![],
+    }
+}
+
+fn now() -> SystemTime {
+    httpdate::parse_http_date("Sun, 18 Oct 2026 12:00:00 GMT").unwrap()
+}
+
+#[test]
+fn uses_max_age_minus_age() {
+    let response = response(&[
+        ("Cache-Control", "public, max-age=21600, must-revalidate"),
+        ("Age", "600"),
+    ]);
+
+    assert_eq!(
+        freshness_lifetime(&response, now()),
+        Duration::from_secs(21000)
+    );
+}
+
+#[test]
+fn falls_back_to_s_maxage() {
+    let response = response(&[("cache-control", "public, s-maxage=300")]);
+
+    assert_eq!(
+        freshness_lifetime(&response, now()),
+        Duration::from_secs(300)
+    );
+}
+
+#[test]
+fn no_cache_is_never_fresh() {
+    let response = response(&[("Cache-Control", "no-cache, max-age=3600")]);
+
+    assert_eq!(freshness_lifetime(&response, now()), Duration::ZERO);
+}
+
+#[test]
+fn uses_expires_relative_to_date() {
+    let response = response(&[
+        ("Date", "Sun, 18 Oct 2026 11:59:00 GMT"),
+        ("Expires", "Sun, 18 Oct 2026 13:00:00 GMT"),
+    ]);
+
+    // One hour and one minute of lifetime, one minute of which already passed.
+    assert_eq!(
+        freshness_lifetime(&response, now()),
+        Duration::from_secs(3600)
+    );
+}
+
+#[test]
+fn invalid_expires_is_stale() {
+    let response = response(&[("Expires", "0")]);
+
+    assert_eq!(freshness_lifetime(&response, now()), Duration::ZERO);
+}
+
+#[test]
+fn missing_headers_use_fallback_lifetime() {
+    assert_eq!(
+        freshness_lifetime(&response(&[]), now()),
+        Duration::from_secs(60)
+    );
+}
+
+#[test]
+fn refresh_delay_applies_fraction_jitter_and_bounds() {
+    let policy = RefreshPolicy {
+        fraction: 0.8,
+        jitter: 0.1,
+        min_delay: Duration::from_secs(30),
+        max_delay: Duration::from_secs(3600),
+    };
+
+    for _ in 0..100 {
+        let delay = policy.refresh_delay(Duration::from_secs(1000));
+        assert!(delay <= Duration::from_secs(800));
+        assert!(delay >= Duration::from_secs(720));
+    }
+    assert_eq!(
+        policy.refresh_delay(Duration::ZERO),
+        Duration::from_secs(30)
+    );
+    assert_eq!(
+        policy.refresh_delay(Duration::from_secs(u64::MAX)),
+        Duration::from_secs(3600)
+    );
+}
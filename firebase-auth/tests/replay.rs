@@ -0,0 +1,184 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use firebase_auth::{
+    InMemoryReplayStore, ReplayGuard, ReplayStore, VerificationError, VerifyOptions,
+};
+use futures::future::BoxFuture;
+use serde_json::Value;
+
+fn token(uid: &str) -> String {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["sub"] = uid.into();
+    common::sign(&claims)
+}
+
+#[tokio::test]
+async fn accepts_a_token_only_once() {
+    let auth = common::firebase_auth();
+    let token = token("user-1");
+
+    let claims: Value = auth.verify_once(&token).await.unwrap();
+    assert_eq!(claims["sub"], "user-1");
+
+    assert!(matches!(
+        auth.verify_once::<Value>(&token).await,
+        Err(VerificationError::TokenReplayed)
+    ));
+    assert!(auth.clone().verify_once::<Value>(&token).await.is_err());
+    assert!(auth.verify::<Value>(&token).is_ok());
+}
+
+#[tokio::test]
+async fn accepts_other_tokens() {
+    let auth = common::firebase_auth();
+
+    assert!(auth.verify_once::<Value>(&token("user-1")).await.is_ok());
+    assert!(auth.verify_once::<Value>(&token("user-2")).await.is_ok());
+}
+
+#[derive(Clone, Default)]
+struct RecordingStore {
+    inserted: Arc<Mutex<Vec<(String, u64)>>>,
+}
+
+impl ReplayStore for RecordingStore {
+    fn insert_once<'a>(&'a self, key: &'a str, expires_at: u64) -> BoxFuture<'a, bool> {
+        let mut inserted = self.inserted.lock().unwrap();
+        let first = !inserted.iter().any(|(k, _)| k == key);
+        inserted.push((key.to_owned(), expires_at));
+        Box::pin(async move { first })
+    }
+}
+
+#[tokio::test]
+async fn uses_a_custom_store_keyed_by_token_hash() {
+    let store = RecordingStore::default();
+    let auth = common::firebase_auth().with_replay_guard(ReplayGuard::new(store.clone()));
+    let token = token("user-1");
+
+    let claims: Value = auth.verify_once(&token).await.unwrap();
+
+    let inserted = store.inserted.lock().unwrap();
+    assert_eq!(inserted.len(), 1);
+    assert_eq!(inserted[0].0.len(), 64);
+    assert!(!inserted[0].0.contains(&token));
+    assert_eq!(Some(inserted[0].1), claims["exp"].as_u64());
+}
+
+#[tokio::test]
+async fn does_not_remember_rejected_tokens() {
+    let store = RecordingStore::default();
+    let auth = common::firebase_auth().with_replay_guard(ReplayGuard::new(store.clone()));
+    let mut claims = common::firebase_claims("other-project");
+    claims["sub"] = "user-1".into();
+
+    let result = auth.verify_once::<Value>(&common::sign(&claims)).await;
+
+    assert!(matches!(result, Err(VerificationError::InvalidToken)));
+    assert!(store.inserted.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn in_memory_store_forgets_expired_and_evicted_tokens() {
+    let store = InMemoryReplayStore::new(1);
+
+    assert!(store.insert_once("a", 1).await);
+    assert!(store.insert_once("a", u64::MAX).await);
+    assert!(!store.insert_once("a", u64::MAX).await);
+
+    assert!(store.insert_once("b", u64::MAX).await);
+    assert!(store.insert_once("a", u64::MAX).await);
+}
+
+#[tokio::test]
+async fn applies_the_verify_options_once() {
+    let auth = common::firebase_auth();
+    let options = VerifyOptions {
+        required_claims: vec!["email".to_owned()],
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        auth.verify_once_with::<Value>(&token("user-1"), &options)
+            .await,
+        Err(VerificationError::MissingClaim)
+    ));
+
+    let token = token("user-2");
+    assert!(auth
+        .verify_once_with::<Value>(&token, &VerifyOptions::default())
+        .await
+        .is_ok());
+    assert!(matches!(
+        auth.verify_once_with::<Value>(&token, &VerifyOptions::default())
+            .await,
+        Err(VerificationError::TokenReplayed)
+    ));
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_extractor_rejects_a_replayed_token() {
+    use axum::{
+        extract::FromRequestParts,
+        http::{request::Parts, Request, StatusCode},
+        response::IntoResponse,
+    };
+    use firebase_auth::{FirebaseAuthState, FirebaseUser, SingleUse};
+
+    let state = FirebaseAuthState::new(common::firebase_auth());
+    let token = token("user-1");
+    let request = || -> Parts {
+        Request::builder()
+            .header("Authorization", format!("Bearer {}", token))
+            .body(())
+            .unwrap()
+            .into_parts()
+            .0
+    };
+
+    let SingleUse(user) = SingleUse::<FirebaseUser>::from_request_parts(&mut request(), &state)
+        .await
+        .ok()
+        .unwrap();
+    assert_eq!(user.sub, "user-1");
+
+    let response = SingleUse::<FirebaseUser>::from_request_parts(&mut request(), &state)
+        .await
+        .err()
+        .unwrap()
+        .into_response();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[cfg(feature = "actix-web")]
+#[actix_web::test]
+async fn actix_extractor_rejects_a_replayed_token() {
+    use actix_web::{http::StatusCode, test::TestRequest, web::Data, FromRequest};
+    use firebase_auth::{FirebaseUser, SingleUse};
+
+    let firebase_auth = Data::new(common::firebase_auth());
+    let token = token("user-1");
+    let request = || {
+        TestRequest::default()
+            .app_data(firebase_auth.clone())
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_http_request()
+    };
+
+    let SingleUse(user) = SingleUse::<FirebaseUser>::extract(&request())
+        .await
+        .unwrap();
+    assert_eq!(user.sub, "user-1");
+
+    let error = SingleUse::<FirebaseUser>::extract(&request())
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.as_response_error().status_code(),
+        StatusCode::UNAUTHORIZED
+    );
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use common::InMemoryKeyFetcher;
+use firebase_auth::{FirebaseAuth, Spawner};
+use futures::{channel::oneshot, executor::block_on, future::BoxFuture};
+
+/// Runs every task on its own thread, without any async runtime.
+struct ThreadSpawner;
+
+impl Spawner for ThreadSpawner {
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        thread::spawn(move || block_on(task));
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || {
+            thread::sleep(duration);
+            let _ = sender.send(());
+        });
+        Box::pin(async move {
+            let _ = receiver.await;
+        })
+    }
+}
+
+#[test]
+fn refreshes_with_a_custom_spawner() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = block_on(
+        FirebaseAuth::builder(common::config("memory://jwks"))
+            .key_fetcher(key_fetcher.clone())
+            .refresh_policy(common::refresh_policy(Duration::from_millis(10)))
+            .spawner(ThreadSpawner)
+            .build(),
+    )
+    .unwrap();
+
+    thread::sleep(Duration::from_millis(100));
+
+    assert!(key_fetcher.requests() > 1);
+    assert!(auth.status().refresh_task_running);
+
+    block_on(auth.shutdown());
+    let requests = key_fetcher.requests();
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(key_fetcher.requests(), requests);
+}
+
+#[tokio::test]
+async fn manual_refresh_only_fetches_on_request() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .refresh_policy(common::refresh_policy(Duration::from_millis(10)))
+        .manual_refresh()
+        .build()
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(key_fetcher.requests(), 1);
+    assert!(!auth.status().refresh_task_running);
+
+    let delay = auth.refresh_keys().await;
+
+    assert_eq!(delay, Duration::from_millis(10));
+    assert_eq!(key_fetcher.requests(), 2);
+}
+
+#[tokio::test]
+async fn manual_refresh_returns_the_backoff_delay_on_failure() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .manual_refresh()
+        .build()
+        .await
+        .unwrap();
+
+    key_fetcher.set_status(503);
+    let delay = auth.refresh_keys().await;
+
+    assert!(delay <= Duration::from_secs(1));
+    assert_eq!(auth.status().consecutive_failures, 1);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use std::time::Duration;
+
+use firebase_auth::{FirebaseAuth, PublicKeysError};
+use serde_json::{json, Value};
+
+const PROJECT_ID: &str = "my-project";
+
+#[test]
+fn verifies_with_static_jwks_document() {
+    let auth = FirebaseAuth::from_static_keys(PROJECT_ID, &common::jwks().to_string()).unwrap();
+
+    let user: firebase_auth::FirebaseUser = auth
+        .verify(&common::sign(&common::firebase_claims(PROJECT_ID)))
+        .unwrap();
+
+    assert_eq!(user.user_id, "user-1");
+}
+
+#[test]
+fn verifies_with_static_certificates() {
+    let certificates = json!({ common::KID: common::RSA_CERTIFICATE });
+    let auth = FirebaseAuth::from_static_keys(PROJECT_ID, &certificates.to_string()).unwrap();
+
+    let claims: Value = auth
+        .verify(&common::sign(&common::firebase_claims(PROJECT_ID)))
+        .unwrap();
+
+    assert_eq!(claims["sub"], "user-1");
+}
+
+#[test]
+fn rejects_invalid_certificate() {
+    let certificates =
+        json!({ common::KID: "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----" });
+    let result = FirebaseAuth::from_static_keys(PROJECT_ID, &certificates.to_string());
+
+    assert!(matches!(
+        result.err(),
+        Some(PublicKeysError::CannotParseCertificate)
+    ));
+}
+
+#[test]
+fn rejects_unknown_document() {
+    let result = FirebaseAuth::from_static_keys(PROJECT_ID, "[1, 2, 3]");
+
+    assert!(matches!(
+        result.err(),
+        Some(PublicKeysError::CannotParsePublicKey(_))
+    ));
+}
+
+#[tokio::test]
+async fn refresh_keeps_the_static_keys() {
+    let base_url =
+        common::serve_json(|_| vec![("/jwks.json".to_string(), json!({ "keys": [] }))]).await;
+    let auth = FirebaseAuth::with_static_keys(
+        common::config(&format!("{}/jwks.json", base_url)),
+        serde_json::from_value(common::jwks()["keys"].clone()).unwrap(),
+    );
+
+    assert_eq!(auth.refresh_keys().await, Duration::MAX);
+
+    let claims: Value = auth.verify(&common::sign(&common::claims())).unwrap();
+    assert_eq!(claims["sub"], "user-1");
+    assert!(auth.status().last_error.is_none());
+}
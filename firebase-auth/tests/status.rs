@@ -0,0 +1,90 @@
+mod common;
+
+use std::time::Duration;
+
+use common::InMemoryKeyFetcher;
+use firebase_auth::{FirebaseAuth, RefreshEvent};
+
+#[tokio::test]
+async fn reports_the_initial_keys() {
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(InMemoryKeyFetcher::new(200))
+        .build()
+        .await
+        .unwrap();
+
+    let status = auth.status();
+
+    assert_eq!(status.kids, vec![common::KID.to_string()]);
+    assert_eq!(status.consecutive_failures, 0);
+    assert!(status.last_refresh.is_some());
+    assert!(status.next_refresh.is_some());
+    assert!(status.refresh_task_running);
+    assert!(matches!(
+        &*auth.subscribe().borrow(),
+        RefreshEvent::Loaded { kids } if kids == &[common::KID.to_string()]
+    ));
+}
+
+#[tokio::test]
+async fn publishes_refresh_failures_and_recovery() {
+    let key_fetcher = InMemoryKeyFetcher::new(200);
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .refresh_policy(common::refresh_policy(Duration::from_millis(20)))
+        .build()
+        .await
+        .unwrap();
+    let mut events = auth.subscribe();
+
+    key_fetcher.set_status(503);
+    events.changed().await.unwrap();
+    assert!(matches!(
+        &*events.borrow_and_update(),
+        RefreshEvent::Failed {
+            consecutive_failures: 1,
+            ..
+        }
+    ));
+    let status = auth.status();
+    assert_eq!(status.consecutive_failures, 1);
+    assert!(status.last_error.unwrap().contains("503"));
+
+    key_fetcher.set_status(200);
+    loop {
+        events.changed().await.unwrap();
+        if let RefreshEvent::Refreshed { .. } = &*events.borrow_and_update() {
+            break;
+        }
+    }
+    let status = auth.status();
+    assert_eq!(status.consecutive_failures, 0);
+    assert!(status.last_error.is_none());
+}
+
+#[test]
+fn static_keys_have_no_scheduled_refresh() {
+    let document = common::jwks().to_string();
+    let auth = FirebaseAuth::from_static_keys(common::AUDIENCE, &document).unwrap();
+
+    let status = auth.status();
+
+    assert_eq!(status.kids, vec![common::KID.to_string()]);
+    assert!(status.next_refresh.is_none());
+    assert!(!status.refresh_task_running);
+}
+
+#[tokio::test]
+async fn unrepresentable_refresh_time_is_not_scheduled() {
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(InMemoryKeyFetcher::new(200))
+        .refresh_policy(common::refresh_policy(Duration::MAX))
+        .build()
+        .await
+        .unwrap();
+
+    let status = auth.status();
+
+    assert!(status.last_refresh.is_some());
+    assert!(status.next_refresh.is_none());
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "testing")]
+
+use std::{sync::OnceLock, time::Duration};
+
+use firebase_auth::{testing::TestIssuer, FirebaseUser, VerificationError};
+use serde_json::Value;
+
+const PROJECT_ID: &str = "my-project";
+
+fn issuer() -> &'static TestIssuer {
+    static ISSUER: OnceLock<TestIssuer> = OnceLock::new();
+    ISSUER.get_or_init(|| TestIssuer::new(PROJECT_ID))
+}
+
+#[test]
+fn verifies_minted_tokens() {
+    let token = issuer()
+        .token("user-1")
+        .claim("email", "user@example.com")
+        .sign_in_provider("google.com")
+        .sign();
+
+    let auth = issuer().firebase_auth();
+    let user: FirebaseUser = auth.verify(&token).unwrap();
+    let claims: Value = auth.verify(&token).unwrap();
+
+    assert_eq!(user.user_id, "user-1");
+    assert_eq!(user.email.as_deref(), Some("user@example.com"));
+    assert_eq!(claims["firebase"]["sign_in_provider"], "google.com");
+}
+
+#[test]
+fn rejects_expired_tokens_and_other_audiences() {
+    let auth = issuer().firebase_auth();
+    let expired = issuer().token("user-1").expires_at(1_000_000).sign();
+    let other_audience = issuer().token("user-1").audience("other-project").sign();
+
+    assert!(matches!(
+        auth.verify::<FirebaseUser>(&expired),
+        Err(VerificationError::InvalidToken)
+    ));
+    assert!(matches!(
+        auth.verify::<FirebaseUser>(&other_audience),
+        Err(VerificationError::InvalidToken)
+    ));
+}
+
+#[test]
+fn rejects_unknown_kids() {
+    let token = issuer()
+        .token("user-1")
+        .kid("unknown")
+        .expires_in(Duration::from_secs(60))
+        .sign();
+
+    assert!(matches!(
+        issuer().firebase_auth().verify::<FirebaseUser>(&token),
+        Err(VerificationError::NotfoundMatchKid)
+    ));
+}
+
+#[test]
+fn publishes_the_public_key() {
+    let keys = firebase_auth::parse_key_document(&issuer().jwks_document()).unwrap();
+
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].kid, issuer().kid());
+    assert_eq!(issuer().jwk_keys().keys[0].n, keys[0].n);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use firebase_auth::FirebaseAuth;
+use serde_json::Value;
+use tracing_subscriber::fmt::{format::FmtSpan, MakeWriter};
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = CapturedLogs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+fn verify_with_logs(token: &str) -> String {
+    logs_of(|auth| {
+        let _ = auth.verify::<Value>(token);
+    })
+}
+
+fn logs_of(f: impl FnOnce(&FirebaseAuth)) -> String {
+    let document = common::jwks().to_string();
+    let auth = FirebaseAuth::from_static_keys(common::AUDIENCE, &document).unwrap();
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(logs.clone())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .finish();
+    tracing::subscriber::with_default(subscriber, || f(&auth));
+    logs.contents()
+}
+
+#[test]
+fn records_verified_claims_without_the_token() {
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+
+    let logs = verify_with_logs(&token);
+
+    assert!(logs.contains("verify_token"));
+    assert!(logs.contains("uid=\"user-1\""));
+    assert!(logs.contains("sign_in_provider=\"password\""));
+    assert!(logs.contains(&format!("kid=\"{}\"", common::KID)));
+    assert!(logs.contains("outcome=\"ok\""));
+    assert!(!logs.contains(&token));
+    for part in token.split('.') {
+        assert!(!logs.contains(part));
+    }
+}
+
+#[test]
+fn records_only_the_outcome_of_rejected_tokens() {
+    let token = common::sign(&common::firebase_claims("other-project"));
+
+    let logs = verify_with_logs(&token);
+
+    assert!(logs.contains("outcome=\"InvalidToken\""));
+    assert!(!logs.contains("uid="));
+    assert!(!logs.contains(&token));
+}
+
+#[test]
+fn records_each_token_of_a_batch() {
+    let valid = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let rejected = common::sign(&common::firebase_claims("other-project"));
+
+    let logs = logs_of(|auth| {
+        let _ = auth.verify_many::<Value>(&[&valid, &rejected]);
+    });
+
+    assert!(logs.contains("verify_tokens"));
+    assert!(logs.contains("rejected=1"));
+    assert_eq!(logs.matches("verify_token{").count(), 2);
+    assert!(logs.contains("uid=\"user-1\""));
+    assert!(logs.contains("outcome=\"ok\""));
+    assert!(logs.contains("outcome=\"InvalidToken\""));
+    assert!(!logs.contains(&valid));
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use firebase_auth::{
+    FirebaseAuth, KeyFetchResponse, KeyFetcher, PublicKeysError, VerificationCacheStats,
+    VerificationError, VerifyOptions,
+};
+use futures::future::BoxFuture;
+use serde_json::{json, Value};
+
+fn cached_firebase_auth() -> FirebaseAuth {
+    common::firebase_auth().with_verification_cache(100)
+}
+
+fn stats(auth: &FirebaseAuth) -> VerificationCacheStats {
+    auth.cache_stats().unwrap()
+}
+
+#[test]
+fn returns_cached_claims_for_repeat_tokens() {
+    let auth = cached_firebase_auth();
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+
+    let first: Value = auth.verify(&token).unwrap();
+    let second: Value = auth.verify(&token).unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(
+        stats(&auth),
+        VerificationCacheStats {
+            hits: 1,
+            misses: 1,
+            entries: 1,
+        }
+    );
+}
+
+#[test]
+fn is_disabled_by_default() {
+    let auth = common::firebase_auth();
+
+    assert!(auth.cache_stats().is_none());
+}
+
+#[test]
+fn does_not_cache_rejected_tokens() {
+    let auth = cached_firebase_auth();
+    let token = common::sign(&common::firebase_claims("other-project"));
+
+    for _ in 0..2 {
+        assert!(matches!(
+            auth.verify::<Value>(&token),
+            Err(VerificationError::InvalidToken)
+        ));
+    }
+    assert_eq!(stats(&auth).hits, 0);
+    assert_eq!(stats(&auth).entries, 0);
+}
+
+#[test]
+fn does_not_cache_tokens_accepted_within_the_leeway() {
+    let auth = cached_firebase_auth();
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["exp"] = (common::now() - 10).into();
+
+    let _: Value = auth.verify(&common::sign(&claims)).unwrap();
+
+    assert_eq!(stats(&auth).entries, 0);
+}
+
+#[test]
+fn evicts_the_least_recently_used_tokens() {
+    let auth = common::firebase_auth().with_verification_cache(1);
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    let first = common::sign(&claims);
+    claims["sub"] = "user-2".into();
+    let second = common::sign(&claims);
+
+    let _: Value = auth.verify(&first).unwrap();
+    let _: Value = auth.verify(&second).unwrap();
+    let _: Value = auth.verify(&first).unwrap();
+
+    assert_eq!(stats(&auth).hits, 0);
+    assert_eq!(stats(&auth).entries, 1);
+}
+
+#[test]
+fn applies_the_verify_options_to_cached_claims() {
+    let auth = cached_firebase_auth();
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let options = VerifyOptions {
+        required_claims: vec!["admin".to_owned()],
+        ..Default::default()
+    };
+
+    let _: Value = auth.verify(&token).unwrap();
+
+    assert!(matches!(
+        auth.verify_with::<Value>(&token, &options),
+        Err(VerificationError::MissingClaim)
+    ));
+    assert_eq!(stats(&auth).hits, 1);
+}
+
+#[test]
+fn bypasses_the_cache_for_a_custom_leeway() {
+    let auth = cached_firebase_auth();
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let options = VerifyOptions {
+        leeway: Some(Duration::ZERO),
+        ..Default::default()
+    };
+
+    let _: Value = auth.verify(&token).unwrap();
+    let _: Value = auth.verify_with(&token, &options).unwrap();
+
+    assert_eq!(stats(&auth).hits, 0);
+    assert_eq!(stats(&auth).misses, 1);
+}
+
+/// Serve the key set in `document`, which the test replaces to rotate the keys.
+#[derive(Clone)]
+struct RotatingKeyFetcher {
+    document: Arc<Mutex<Value>>,
+}
+
+impl KeyFetcher for RotatingKeyFetcher {
+    fn fetch<'a>(
+        &'a self,
+        _url: &'a str,
+    ) -> BoxFuture<'a, Result<KeyFetchResponse, PublicKeysError>> {
+        let body = self.document.lock().unwrap().to_string().into_bytes();
+        Box::pin(async move {
+            Ok(KeyFetchResponse {
+                status: 200,
+                headers: vec![("cache-control".to_string(), "max-age=3600".to_string())],
+                body,
+            })
+        })
+    }
+}
+
+fn key_set(kids: &[&str]) -> Value {
+    let key = &common::jwks()["keys"][0];
+    let keys: Vec<Value> = kids
+        .iter()
+        .map(|kid| {
+            let mut key = key.clone();
+            key["kid"] = (*kid).into();
+            key
+        })
+        .collect();
+    json!({ "keys": keys })
+}
+
+#[tokio::test]
+async fn invalidates_cached_claims_when_the_keys_rotate() {
+    let key_fetcher = RotatingKeyFetcher {
+        document: Arc::new(Mutex::new(key_set(&[common::KID]))),
+    };
+    let auth = FirebaseAuth::builder(common::config("memory://jwks"))
+        .key_fetcher(key_fetcher.clone())
+        .manual_refresh()
+        .build()
+        .await
+        .unwrap()
+        .with_verification_cache(100);
+    let token = common::sign(&common::claims());
+
+    let _: Value = auth.verify(&token).unwrap();
+    let _: Value = auth.verify(&token).unwrap();
+    assert_eq!(stats(&auth).hits, 1);
+
+    // Unchanged kids keep the cache.
+    auth.refresh_keys().await;
+    let _: Value = auth.verify(&token).unwrap();
+    assert_eq!(stats(&auth).hits, 2);
+
+    *key_fetcher.document.lock().unwrap() = key_set(&["rotated-key"]);
+    auth.refresh_keys().await;
+
+    assert!(matches!(
+        auth.verify::<Value>(&token),
+        Err(VerificationError::NotfoundMatchKid)
+    ));
+    assert_eq!(stats(&auth).hits, 2);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use std::time::Duration;
+
+use firebase_auth::{VerificationError, VerifiedToken, VerifyOptions};
+use jsonwebtoken::Algorithm;
+use serde_json::Value;
+
+#[test]
+fn returns_the_claims_with_the_signature_details() {
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+
+    let verified: VerifiedToken<Value> = common::firebase_auth().verify_token(&token).unwrap();
+
+    assert_eq!(verified.claims["sub"], "user-1");
+    assert_eq!(verified.kid, common::KID);
+    assert_eq!(verified.algorithm, Algorithm::RS256);
+    assert_eq!(verified.token, token);
+    assert_eq!(
+        verified.expires_at,
+        verified.claims["exp"].as_u64().unwrap()
+    );
+}
+
+#[test]
+fn computes_the_remaining_lifetime() {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["exp"] = (common::now() + 600).into();
+
+    let verified: VerifiedToken<Value> = common::firebase_auth()
+        .verify_token(&common::sign(&claims))
+        .unwrap();
+
+    let remaining = verified.remaining_lifetime();
+    assert!(remaining <= Duration::from_secs(600));
+    assert!(remaining > Duration::from_secs(590));
+}
+
+#[test]
+fn remaining_lifetime_is_zero_within_the_leeway() {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["exp"] = (common::now() - 10).into();
+
+    let verified: VerifiedToken<Value> = common::firebase_auth()
+        .verify_token(&common::sign(&claims))
+        .unwrap();
+
+    assert_eq!(verified.remaining_lifetime(), Duration::ZERO);
+}
+
+#[test]
+fn applies_the_verify_options() {
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let options = VerifyOptions {
+        required_claims: vec!["admin".to_owned()],
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        common::firebase_auth().verify_token_with::<Value>(&token, &options),
+        Err(VerificationError::MissingClaim)
+    ));
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_extractor_returns_the_verified_token() {
+    use axum::{extract::FromRequestParts, http::Request};
+    use firebase_auth::{FirebaseAuthState, FirebaseUser};
+
+    let state = FirebaseAuthState::new(common::firebase_auth());
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let (mut parts, _) = Request::builder()
+        .header("Authorization", format!("Bearer {}", token))
+        .body(())
+        .unwrap()
+        .into_parts();
+
+    let verified = VerifiedToken::<FirebaseUser>::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .unwrap();
+
+    assert_eq!(verified.claims.sub, "user-1");
+    assert_eq!(verified.kid, common::KID);
+}
+
+#[cfg(feature = "actix-web")]
+#[actix_web::test]
+async fn actix_extractor_returns_the_verified_token() {
+    use actix_web::{test::TestRequest, web::Data, FromRequest};
+    use firebase_auth::FirebaseUser;
+
+    let firebase_auth = Data::new(common::firebase_auth());
+    let token = common::sign(&common::firebase_claims(common::AUDIENCE));
+    let request = TestRequest::default()
+        .app_data(firebase_auth.clone())
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_http_request();
+
+    let verified = VerifiedToken::<FirebaseUser>::extract(&request)
+        .await
+        .unwrap();
+
+    assert_eq!(verified.claims.sub, "user-1");
+    assert_eq!(verified.kid, common::KID);
+
+    let expired = {
+        let mut claims = common::firebase_claims(common::AUDIENCE);
+        claims["exp"] = (common::now() - 3600).into();
+        common::sign(&claims)
+    };
+    let request = TestRequest::default()
+        .app_data(firebase_auth)
+        .insert_header(("Authorization", format!("Bearer {}", expired)))
+        .to_http_request();
+    let error = VerifiedToken::<FirebaseUser>::extract(&request)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.as_response_error().status_code(),
+        actix_web::http::StatusCode::UNAUTHORIZED
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use firebase_auth::{VerificationError, VerifyOptions};
+use serde_json::Value;
+
+fn token_for(uid: usize) -> String {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["sub"] = format!("user-{}", uid).into();
+    common::sign(&claims)
+}
+
+#[test]
+fn returns_the_results_in_order() {
+    let valid: Vec<String> = (0..50).map(token_for).collect();
+    let invalid = common::sign(&common::firebase_claims("other-project"));
+    let mut tokens: Vec<&str> = valid.iter().map(String::as_str).collect();
+    tokens.insert(17, &invalid);
+    tokens.push("not-a-token");
+
+    let results = common::firebase_auth().verify_many::<Value>(&tokens);
+
+    assert_eq!(results.len(), 52);
+    for (i, result) in results.iter().enumerate() {
+        match i {
+            17 => assert!(matches!(result, Err(VerificationError::InvalidToken))),
+            51 => assert!(result.is_err()),
+            _ => {
+                let uid = if i < 17 { i } else { i - 1 };
+                assert_eq!(result.as_ref().unwrap()["sub"], format!("user-{}", uid));
+            }
+        }
+    }
+}
+
+#[test]
+fn verifies_an_empty_batch() {
+    assert!(common::firebase_auth().verify_many::<Value>(&[]).is_empty());
+}
+
+#[test]
+fn applies_the_verify_options_to_each_token() {
+    let mut admin_claims = common::firebase_claims(common::AUDIENCE);
+    admin_claims["admin"] = true.into();
+    let admin = common::sign(&admin_claims);
+    let user = token_for(1);
+    let options = VerifyOptions {
+        required_claims: vec!["admin".to_owned()],
+        ..Default::default()
+    };
+
+    let results = common::firebase_auth().verify_many_with::<Value>(&[&admin, &user], &options);
+
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(VerificationError::MissingClaim)));
+}
+
+#[test]
+fn uses_the_verification_cache() {
+    let auth = common::firebase_auth().with_verification_cache(100);
+    let token = token_for(1);
+
+    let _: Value = auth.verify(&token).unwrap();
+    let results = auth.verify_many::<Value>(&[&token, &token]);
+
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(auth.cache_stats().unwrap().hits, 2);
+}
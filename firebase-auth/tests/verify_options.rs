@@ -0,0 +1,212 @@
+mod common;
+
+use std::time::Duration;
+
+use firebase_auth::{VerificationError, VerifyOptions};
+use serde_json::Value;
+
+fn token_authenticated_ago(seconds: u64) -> String {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["auth_time"] = (common::now() - seconds).into();
+    common::sign(&claims)
+}
+
+fn recent_login() -> VerifyOptions {
+    VerifyOptions {
+        max_auth_age: Some(Duration::from_secs(300)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn accepts_recent_authentication() {
+    let claims: Value = common::firebase_auth()
+        .verify_with(&token_authenticated_ago(60), &recent_login())
+        .unwrap();
+
+    assert_eq!(claims["sub"], "user-1");
+}
+
+#[test]
+fn rejects_stale_authentication() {
+    let token = token_authenticated_ago(3600);
+
+    assert!(matches!(
+        common::firebase_auth().verify_with::<Value>(&token, &recent_login()),
+        Err(VerificationError::StaleAuthentication)
+    ));
+    assert!(common::firebase_auth().verify::<Value>(&token).is_ok());
+}
+
+#[test]
+fn rejects_tokens_without_auth_time() {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims.as_object_mut().unwrap().remove("auth_time");
+
+    assert!(matches!(
+        common::firebase_auth().verify_with::<Value>(&common::sign(&claims), &recent_login()),
+        Err(VerificationError::StaleAuthentication)
+    ));
+}
+
+fn verify_with(claims: &Value, options: VerifyOptions) -> Result<Value, VerificationError> {
+    common::firebase_auth().verify_with(&common::sign(claims), &options)
+}
+
+#[test]
+fn accepts_additional_audiences() {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["aud"] = "other-client".into();
+
+    assert!(verify_with(&claims, VerifyOptions::default()).is_err());
+    let options = VerifyOptions {
+        audiences: vec!["other-client".to_string()],
+        ..Default::default()
+    };
+    assert!(verify_with(&claims, options).is_ok());
+}
+
+#[test]
+fn applies_the_leeway_to_expiry() {
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["exp"] = (common::now() - 30).into();
+
+    assert!(verify_with(&claims, VerifyOptions::default()).is_ok());
+    let options = VerifyOptions {
+        leeway: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    assert!(matches!(
+        verify_with(&claims, options),
+        Err(VerificationError::InvalidToken)
+    ));
+}
+
+#[test]
+fn rejects_missing_required_claims() {
+    let options = || VerifyOptions {
+        required_claims: vec!["email".to_string()],
+        ..Default::default()
+    };
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+
+    assert!(matches!(
+        verify_with(&claims, options()),
+        Err(VerificationError::MissingClaim)
+    ));
+    claims["email"] = "user@example.com".into();
+    assert!(verify_with(&claims, options()).is_ok());
+}
+
+#[test]
+fn rejects_old_tokens() {
+    let options = VerifyOptions {
+        max_token_age: Some(Duration::from_secs(60)),
+        ..Default::default()
+    };
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["iat"] = (common::now() - 600).into();
+
+    assert!(matches!(
+        verify_with(&claims, options),
+        Err(VerificationError::TokenTooOld)
+    ));
+}
+
+#[test]
+fn restricts_sign_in_providers() {
+    let options = || VerifyOptions {
+        sign_in_providers: vec!["google.com".to_string()],
+        ..Default::default()
+    };
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+
+    assert!(matches!(
+        verify_with(&claims, options()),
+        Err(VerificationError::SignInProviderNotAllowed)
+    ));
+    claims["firebase"]["sign_in_provider"] = "google.com".into();
+    assert!(verify_with(&claims, options()).is_ok());
+}
+
+#[test]
+fn requires_a_verified_email() {
+    let options = || VerifyOptions {
+        require_email_verified: true,
+        ..Default::default()
+    };
+    let mut claims = common::firebase_claims(common::AUDIENCE);
+    claims["email_verified"] = false.into();
+
+    assert!(matches!(
+        verify_with(&claims, options()),
+        Err(VerificationError::EmailNotVerified)
+    ));
+    claims["email_verified"] = true.into();
+    assert!(verify_with(&claims, options()).is_ok());
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_extractors_use_the_options_of_the_route() {
+    use axum::{extract::FromRequestParts, http::Request};
+    use firebase_auth::{FirebaseAuthState, FirebaseUser};
+
+    let state = FirebaseAuthState::new(common::firebase_auth());
+    let request = |options: Option<VerifyOptions>| {
+        let mut request = Request::builder()
+            .header(
+                "Authorization",
+                format!("Bearer {}", token_authenticated_ago(3600)),
+            )
+            .body(())
+            .unwrap();
+        if let Some(options) = options {
+            request.extensions_mut().insert(options);
+        }
+        request.into_parts().0
+    };
+
+    let mut parts = request(None);
+    assert!(FirebaseUser::from_request_parts(&mut parts, &state)
+        .await
+        .is_ok());
+
+    let mut parts = request(Some(recent_login()));
+    assert!(FirebaseUser::from_request_parts(&mut parts, &state)
+        .await
+        .is_err());
+}
+
+#[cfg(feature = "actix-web")]
+#[actix_web::test]
+async fn actix_extractors_use_the_options_of_the_route() {
+    use actix_web::{http::StatusCode, test::TestRequest, web::Data, FromRequest};
+    use firebase_auth::FirebaseUser;
+
+    let firebase_auth = Data::new(common::firebase_auth());
+    let request = |options: Option<VerifyOptions>| {
+        let mut request = TestRequest::default()
+            .app_data(firebase_auth.clone())
+            .insert_header((
+                "Authorization",
+                format!("Bearer {}", token_authenticated_ago(3600)),
+            ));
+        if let Some(options) = options {
+            request = request.app_data(options);
+        }
+        request.to_http_request()
+    };
+
+    assert!(FirebaseUser::extract(&request(None)).await.is_ok());
+
+    let error = FirebaseUser::extract(&request(Some(recent_login())))
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.as_response_error().status_code(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert!(error.to_string().contains("StaleAuthentication"));
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::InMemoryKeyFetcher;
+use firebase_auth::{get_x509_configuration, FirebaseAuth, FirebaseUser, VerificationError};
+use serde_json::json;
+
+const PROJECT_ID: &str = "my-project";
+
+async fn build(certificate: &str) -> FirebaseAuth {
+    let mut key_fetcher = InMemoryKeyFetcher::new(200);
+    key_fetcher.body = json!({ common::KID: certificate }).to_string().into_bytes();
+
+    FirebaseAuth::builder(get_x509_configuration(PROJECT_ID))
+        .key_fetcher(key_fetcher)
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn verifies_with_keys_from_certificates() {
+    let auth = build(common::RSA_CERTIFICATE).await;
+
+    let token = common::sign(&common::firebase_claims(PROJECT_ID));
+    let user: FirebaseUser = auth.verify(&token).unwrap();
+
+    assert_eq!(user.user_id, "user-1");
+}
+
+#[tokio::test]
+async fn rejects_key_of_expired_certificate() {
+    let auth = build(common::EXPIRED_RSA_CERTIFICATE).await;
+
+    let token = common::sign(&common::firebase_claims(PROJECT_ID));
+    let result: Result<FirebaseUser, VerificationError> = auth.verify(&token);
+
+    assert!(matches!(
+        result,
+        Err(VerificationError::CertificateNotValid)
+    ));
+}